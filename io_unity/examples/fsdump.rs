@@ -15,8 +15,10 @@ use std::io::{BufReader, Write};
 use std::path::PathBuf;
 
 use io_unity::{
-    classes::ClassIDType, type_tree::type_tree_json::set_info_json_tar_reader,
-    unity_asset_view::UnityAssetViewer,
+    classes::ClassIDType,
+    resource::FileOpenMode,
+    type_tree::type_tree_json::set_info_json_tar_reader,
+    unity_asset_view::{BundleLoadMode, UnityAssetViewer},
 };
 
 /// unity extractor
@@ -32,6 +34,18 @@ pub struct Args {
     /// The serialized file.
     #[arg(short, long)]
     serialized_file: Option<String>,
+    /// The WebGL UnityWebData file (*.data or *.data.unityweb).
+    #[arg(short, long)]
+    web_data_file: Option<String>,
+    /// The APK, OBB or other zip file contain Data dir or AssetBundle files.
+    #[arg(short, long)]
+    zip_file: Option<String>,
+    /// Uncompress serialized files in bundles on demand instead of loading them into memory.
+    #[arg(long)]
+    stream: bool,
+    /// Map files into memory instead of reading them through buffered file handles.
+    #[arg(long)]
+    mmap: bool,
     /// The tar zstd compressed file contain type tree info json files
     /// for read file without typetree info.
     /// see https://github.com/DaZombieKiller/TypeTreeDumper
@@ -63,6 +77,15 @@ pub enum Commands {
         #[arg(short, long)]
         out_dir: String,
     },
+    /// Extract raw files (CAB, .resS, .resource) of an AssetBundle file.
+    Unpack {
+        /// The AssetBundle file, or "-" to read it from stdin.
+        #[arg(value_parser)]
+        bundle_file: String,
+        /// The dir save extracted files.
+        #[arg(short, long)]
+        out_dir: String,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -73,12 +96,34 @@ fn main() -> anyhow::Result<()> {
         set_info_json_tar_reader(Box::new(BufReader::new(tar_file)));
     }
 
+    if let Commands::Unpack {
+        bundle_file,
+        out_dir,
+    } = &args.command
+    {
+        let unity_fs = if bundle_file == "-" {
+            UnityFS::read_sequential(std::io::stdin(), None)?
+        } else {
+            let file = OpenOptions::new().read(true).open(bundle_file)?;
+            UnityFS::read(Box::new(BufReader::new(file)), None)?
+        };
+        for out_path in unity_fs.extract_all_to_dir(out_dir)? {
+            println!("{}", out_path.display());
+        }
+        return Ok(());
+    }
+
     let time = std::time::Instant::now();
 
     let mut unity_asset_viewer = UnityAssetViewer::new();
+    if args.stream {
+        unity_asset_viewer.set_bundle_load_mode(BundleLoadMode::Stream);
+    }
+    if args.mmap {
+        unity_asset_viewer.set_file_open_mode(FileOpenMode::Mmap);
+    }
     if let Some(bundle_dir) = args.bundle_dir {
         unity_asset_viewer.read_bundle_dir(&bundle_dir)?;
-
     }
     if let Some(data_dir) = args.data_dir {
         unity_asset_viewer.read_data_dir(data_dir)?;
@@ -88,6 +133,14 @@ fn main() -> anyhow::Result<()> {
         unity_asset_viewer
             .add_serialized_file(Box::new(BufReader::new(file)), Some(".".to_owned()))?;
     }
+    if let Some(web_data_file) = args.web_data_file {
+        let file = OpenOptions::new().read(true).open(web_data_file)?;
+        unity_asset_viewer.add_web_data_file(Box::new(BufReader::new(file)), None)?;
+    }
+    if let Some(zip_file) = args.zip_file {
+        let file = OpenOptions::new().read(true).open(zip_file)?;
+        unity_asset_viewer.add_zip_file(Box::new(BufReader::new(file)))?;
+    }
     println!("Read use {:?}", time.elapsed());
 
     match &args.command {
@@ -243,6 +296,7 @@ fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        Commands::Unpack { .. } => unreachable!(),
     }
 
     Ok(())
@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use crate::serialized_file::Object;
+use crate::unityfs::verify::IntegrityError;
 
 pub type ReadResult<T, E = Error> = core::result::Result<T, E>;
 
@@ -39,6 +40,12 @@ pub enum Error {
     AsSliceError(&'static str),
     #[error("ArrayItemOffset use without field offset.")]
     ArrayItemOffsetError,
+    #[error("object not found. path_id : {0}")]
+    ObjectNotFound(i64),
+    #[error("type not found. type_id : {0}")]
+    TypeNotFound(usize),
+    #[error("UnityFS integrity check failed : {0:?}")]
+    UnityFSIntegrityError(Vec<IntegrityError>),
     #[error("{0}")]
     Other(String),
     #[error("unknown error")]
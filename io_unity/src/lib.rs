@@ -1,7 +1,14 @@
 pub mod classes;
 pub mod error;
+#[cfg(feature = "mmap")]
+pub mod mmap_resource;
+pub mod resource;
 pub mod serialized_file;
+pub mod split_resource;
 pub mod type_tree;
 pub mod unity_asset_view;
+pub mod unity_web_data;
 pub mod unityfs;
 mod until;
+#[cfg(feature = "zip-archive")]
+pub mod zip_archive;
@@ -0,0 +1,100 @@
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{prelude::*, ErrorKind, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Read only memory map of a file.
+///
+/// Clones share the map and keep their own position,
+/// so several threads can read the same file without a lock.
+/// The file must not be modified or truncated while it is mapped.
+#[derive(Clone)]
+pub struct MmapResource {
+    mmap: Arc<Mmap>,
+    current_position: u64,
+}
+
+impl MmapResource {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the map is only sound while no one else write to or truncate the file,
+        // otherwise the data behind the slices handed out can change or the access fault.
+        // Game files are assumed to stay unchanged while they are read,
+        // use `FileOpenMode::Buffered` for files which may be modified.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self {
+            mmap: Arc::new(mmap),
+            current_position: 0,
+        })
+    }
+
+    /// The whole mapped file, slicing it need no syscall.
+    pub fn get_data(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    pub fn get_size(&self) -> u64 {
+        self.mmap.len() as u64
+    }
+}
+
+impl Read for MmapResource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remain = self.fill_buf()?;
+        let read_size = buf.len().min(remain.len());
+        buf[..read_size].copy_from_slice(&remain[..read_size]);
+        self.consume(read_size);
+        Ok(read_size)
+    }
+}
+
+impl BufRead for MmapResource {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        let start = (self.current_position as usize).min(self.mmap.len());
+        Ok(&self.mmap[start..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.current_position += amt as u64;
+    }
+}
+
+impl Seek for MmapResource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::End(offset) => self.mmap.len() as i64 + offset,
+            SeekFrom::Current(offset) => self.current_position as i64 + offset,
+        };
+        if new_pos < 0 {
+            return Err(std::io::Error::from(ErrorKind::InvalidInput));
+        }
+        self.current_position = new_pos as u64;
+        Ok(self.current_position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mmap_resource() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        let path = std::env::temp_dir().join(format!("io_unity_mmap_{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let mut resource = MmapResource::open(&path).unwrap();
+        assert_eq!(resource.get_size(), 5000);
+        assert_eq!(resource.get_data(), &data[..]);
+
+        let mut other_resource = resource.clone();
+        let mut buff = [0u8; 20];
+        resource.seek(SeekFrom::End(-10)).unwrap();
+        assert_eq!(resource.read(&mut buff).unwrap(), 10);
+        assert_eq!(buff[..10], data[4990..]);
+        other_resource.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, data[..20]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(feature = "mmap")]
+use crate::mmap_resource::MmapResource;
+use crate::split_resource::SplitResource;
+use crate::unityfs::UnityResource;
+use std::fs::OpenOptions;
+use std::io::BufReader;
+use std::path::Path;
+
+/// How files are opened from disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FileOpenMode {
+    /// Read files through a [`BufReader`].
+    #[default]
+    Buffered,
+    /// Map files into memory with [`MmapResource`].
+    #[cfg(feature = "mmap")]
+    Mmap,
+}
+
+impl FileOpenMode {
+    pub fn open<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> std::io::Result<Box<dyn UnityResource + Send + Sync>> {
+        match self {
+            FileOpenMode::Buffered => Ok(Box::new(BufReader::new(
+                OpenOptions::new().read(true).open(path)?,
+            ))),
+            #[cfg(feature = "mmap")]
+            FileOpenMode::Mmap => Ok(Box::new(MmapResource::open(path)?)),
+        }
+    }
+}
+
+/// Open the file at `path`, or join its split parts if the file does not exist.
+pub fn open_resource_file<P: AsRef<Path>>(path: P) -> Option<Box<dyn UnityResource + Send + Sync>> {
+    open_resource_file_with_mode(path, FileOpenMode::default())
+}
+
+/// Same as [`open_resource_file`], but open files with `file_open_mode`.
+pub fn open_resource_file_with_mode<P: AsRef<Path>>(
+    path: P,
+    file_open_mode: FileOpenMode,
+) -> Option<Box<dyn UnityResource + Send + Sync>> {
+    if let Ok(file) = file_open_mode.open(path.as_ref()) {
+        return Some(file);
+    }
+    SplitResource::open_with_mode(path, file_open_mode)
+        .ok()
+        .flatten()
+        .map(|split_resource| Box::new(split_resource) as Box<dyn UnityResource + Send + Sync>)
+}
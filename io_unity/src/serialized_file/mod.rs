@@ -21,10 +21,10 @@ pub mod version8;
 pub mod version9;
 
 use std::borrow::Cow;
-use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::io::{prelude::*, SeekFrom};
+use std::io::{prelude::*, Cursor, ErrorKind, SeekFrom};
+use std::sync::{Arc, Mutex};
 
 use binrw::BinRead;
 use binrw::{binrw, BinResult};
@@ -33,19 +33,22 @@ use num_enum::TryFromPrimitive;
 use once_cell::sync::Lazy;
 
 use crate::error::Error;
-#[cfg(feature = "type-tree-json")]
-use crate::type_tree::type_tree_json::get_type_object_args_by_version_class_id;
+#[cfg(feature = "mmap")]
+use crate::mmap_resource::MmapResource;
+use crate::type_tree::provider::{get_default_type_tree_provider, TypeTreeProvider};
 use crate::type_tree::{
-    reader::TypeTreeObjectBinReadArgs, reader::TypeTreeObjectBinReadClassArgs, TypeTreeObject,
+    reader::TypeTreeObjectBinReadArgs, reader::TypeTreeObjectBinReadClassArgs, TypeField,
+    TypeTreeObject,
 };
 use crate::unityfs::UnityResource;
 use crate::until::{Endian, UnityVersion};
 
-use self::version17::FileIdentifier;
+use self::version17::{FileIdentifier, ScriptType};
 
 #[binrw]
 #[brw(repr = u32)]
 #[derive(Debug, Eq, PartialEq, TryFromPrimitive, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u32)]
 #[allow(non_camel_case_types)]
 pub enum SerializedFileFormatVersion {
@@ -129,6 +132,7 @@ pub enum SerializedFileFormatVersion {
 #[binrw]
 #[brw(repr = i32)]
 #[derive(Debug, Eq, PartialEq, TryFromPrimitive, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(i32)]
 #[allow(non_camel_case_types)]
 pub enum BuildTarget {
@@ -300,7 +304,7 @@ static COMMON_STRING: Lazy<HashMap<u32, &'static str>> = Lazy::new(|| {
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct SerializedFileCommonHeader {
     metadata_size: u32,
     file_size: u32,
@@ -309,6 +313,7 @@ pub struct SerializedFileCommonHeader {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SerializedFileMetadata {
     pub version: SerializedFileFormatVersion,
     pub endianess: Endian,
@@ -325,13 +330,138 @@ pub struct Object {
     byte_size: u32,
     pub class: i32,
     type_id: usize,
+    script_type_index: i16,
+}
+
+impl Object {
+    /// Index of the type of object, see [`SerializedFile::add_object`].
+    pub fn get_type_id(&self) -> usize {
+        self.type_id
+    }
+
+    pub fn get_byte_size(&self) -> u32 {
+        self.byte_size
+    }
+
+    /// Index into the script type table for MonoBehaviour objects,
+    /// see [`SerializedFile::get_object_script_type`].
+    pub fn get_script_type_index(&self) -> Option<usize> {
+        usize::try_from(self.script_type_index).ok()
+    }
+}
+
+/// Entry of the type table, fields not stored in the format version are `None`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SerializedTypeInfo {
+    pub class_id: i32,
+    /// Stored in the type entry since format version 17, and per object before.
+    pub script_type_index: Option<usize>,
+    pub is_stripped_type: Option<bool>,
+    /// Hash of the script class, only for MonoBehaviour types.
+    pub script_id: Option<[u8; 16]>,
+    pub old_type_hash: Option<[u8; 16]>,
+    /// 0 if the type tree is not stored.
+    pub type_tree_node_count: usize,
+}
+
+/// Type entry parsed by a format version, read by [`get_type_infos`].
+trait SerializedTypeEntry {
+    fn get_class_id(&self) -> i32;
+
+    /// 0 if the type tree is not stored.
+    fn get_type_tree_node_count(&self) -> usize;
+
+    fn get_script_type_index(&self) -> Option<usize> {
+        None
+    }
+
+    fn is_stripped_type(&self) -> Option<bool> {
+        None
+    }
+
+    fn get_script_id(&self) -> Option<[u8; 16]> {
+        None
+    }
+
+    fn get_old_type_hash(&self) -> Option<[u8; 16]> {
+        None
+    }
+}
+
+fn get_type_infos<T: SerializedTypeEntry>(types: &[T]) -> Vec<SerializedTypeInfo> {
+    types
+        .iter()
+        .map(|stype| SerializedTypeInfo {
+            class_id: stype.get_class_id(),
+            script_type_index: stype.get_script_type_index(),
+            is_stripped_type: stype.is_stripped_type(),
+            script_id: stype.get_script_id(),
+            old_type_hash: stype.get_old_type_hash(),
+            type_tree_node_count: stype.get_type_tree_node_count(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SerializedFileHeaderInfo {
+    pub version: SerializedFileFormatVersion,
+    pub metadata_size: u64,
+    pub file_size: u64,
+    pub data_offset: u64,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExternalFileInfo {
+    pub path: String,
+    pub guid: [u8; 16],
+    pub r#type: i32,
+}
+
+impl From<&FileIdentifier> for ExternalFileInfo {
+    fn from(file_identifier: &FileIdentifier) -> Self {
+        ExternalFileInfo {
+            path: file_identifier.path.to_string(),
+            guid: file_identifier.guid,
+            r#type: file_identifier.r#type,
+        }
+    }
+}
+
+/// Everything stored in the metadata of file except the object table, see [`SerializedFile::get_info`].
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SerializedFileInfo {
+    pub header: SerializedFileHeaderInfo,
+    pub metadata: SerializedFileMetadata,
+    pub user_information: Option<String>,
+    pub types: Vec<SerializedTypeInfo>,
+    pub script_types: Vec<ScriptType>,
+    pub externals: Vec<ExternalFileInfo>,
+    pub object_count: usize,
+}
+
+/// Where object data is read from.
+enum ObjectDataSource {
+    /// Object data is copied out while the reader is locked,
+    /// as readers can not be shared by threads without their position.
+    Reader(Mutex<Box<dyn UnityResource + Send + Sync>>),
+    /// Object data is sliced from the map, without lock or copy.
+    #[cfg(feature = "mmap")]
+    Mmap(MmapResource),
 }
 
 pub struct SerializedFile {
     content: Box<dyn Serialized + Send + Sync>,
-    file_reader: RefCell<Box<dyn UnityResource + Send + Sync>>,
+    object_data_source: ObjectDataSource,
     object_map: BTreeMap<i64, Object>,
+    /// Data of objects replaced or added after read, keyed by path id.
+    object_data_overrides: BTreeMap<i64, Vec<u8>>,
+    is_modified: bool,
     serialized_file_id: i64,
+    type_tree_provider: Option<Arc<dyn TypeTreeProvider>>,
     pub resource_search_path: Option<String>,
 }
 
@@ -344,106 +474,225 @@ impl fmt::Debug for SerializedFile {
 }
 
 impl SerializedFile {
+    /// Read with the provider from [`get_default_type_tree_provider`],
+    /// pass `None` to [`Self::read_with_type_tree_provider`] to read without provider.
     pub fn read(
+        reader: Box<dyn UnityResource + Send + Sync>,
+        serialized_file_id: i64,
+        resource_search_path: Option<String>,
+    ) -> BinResult<Self> {
+        Self::read_with_type_tree_provider(
+            reader,
+            serialized_file_id,
+            resource_search_path,
+            get_default_type_tree_provider(),
+        )
+    }
+
+    /// The provider is used for objects without type tree,
+    /// see [`Self::add_type_trees_to_provider`] to share type trees of this file with it.
+    /// Object data is copied from the locked reader, see [`Self::get_tt_object_by_path_id`].
+    pub fn read_with_type_tree_provider(
         mut reader: Box<dyn UnityResource + Send + Sync>,
         serialized_file_id: i64,
         resource_search_path: Option<String>,
+        type_tree_provider: Option<Arc<dyn TypeTreeProvider>>,
+    ) -> BinResult<Self> {
+        let content = Self::read_content(&mut reader)?;
+        Ok(Self::new(
+            content,
+            ObjectDataSource::Reader(Mutex::new(reader)),
+            serialized_file_id,
+            resource_search_path,
+            type_tree_provider,
+        ))
+    }
+
+    /// Same as [`Self::read_with_type_tree_provider`], but object data is sliced from the map
+    /// instead of copied from a locked reader, so objects are read from several threads without contention.
+    #[cfg(feature = "mmap")]
+    pub fn read_mmap(
+        mmap_resource: MmapResource,
+        serialized_file_id: i64,
+        resource_search_path: Option<String>,
+        type_tree_provider: Option<Arc<dyn TypeTreeProvider>>,
     ) -> BinResult<Self> {
-        let head = SerializedFileCommonHeader::read(&mut reader)?;
+        let content = Self::read_content(&mut mmap_resource.clone())?;
+        Ok(Self::new(
+            content,
+            ObjectDataSource::Mmap(mmap_resource),
+            serialized_file_id,
+            resource_search_path,
+            type_tree_provider,
+        ))
+    }
+
+    fn read_content<R: Read + Seek>(
+        reader: &mut R,
+    ) -> BinResult<Box<dyn Serialized + Send + Sync>> {
+        let head = SerializedFileCommonHeader::read(reader)?;
         reader.seek(SeekFrom::Start(0))?;
         let file: Box<dyn Serialized + Send + Sync> = match head.version {
             SerializedFileFormatVersion::Unsupported => {
-                Box::new(version1::SerializedFile::read(&mut reader)?)
+                Box::new(version1::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_2 => {
-                Box::new(version2::SerializedFile::read(&mut reader)?)
+                Box::new(version2::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_3 => {
-                Box::new(version3::SerializedFile::read(&mut reader)?)
+                Box::new(version3::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_5 => {
-                Box::new(version5::SerializedFile::read(&mut reader)?)
+                Box::new(version5::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_6 => {
-                Box::new(version6::SerializedFile::read(&mut reader)?)
+                Box::new(version6::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_7 => {
-                Box::new(version7::SerializedFile::read(&mut reader)?)
+                Box::new(version7::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_8 => {
-                Box::new(version8::SerializedFile::read(&mut reader)?)
+                Box::new(version8::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_9 => {
-                Box::new(version9::SerializedFile::read(&mut reader)?)
+                Box::new(version9::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_10 => {
-                Box::new(version10::SerializedFile::read(&mut reader)?)
+                Box::new(version10::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::HasScriptTypeIndex => {
-                Box::new(version11::SerializedFile::read(&mut reader)?)
+                Box::new(version11::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_12 => {
-                Box::new(version12::SerializedFile::read(&mut reader)?)
+                Box::new(version12::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::HasTypeTreeHashes => {
-                Box::new(version13::SerializedFile::read(&mut reader)?)
+                Box::new(version13::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::Unknown_14 => {
-                Box::new(version14::SerializedFile::read(&mut reader)?)
+                Box::new(version14::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::SupportsStrippedObject => {
-                Box::new(version15::SerializedFile::read(&mut reader)?)
+                Box::new(version15::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::RefactoredClassId => {
-                Box::new(version16::SerializedFile::read(&mut reader)?)
+                Box::new(version16::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::RefactorTypeData => {
-                Box::new(version17::SerializedFile::read(&mut reader)?)
+                Box::new(version17::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::RefactorShareableTypeTreeData => {
-                Box::new(version17::SerializedFile::read(&mut reader)?)
+                Box::new(version17::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::TypeTreeNodeWithTypeFlags => {
-                Box::new(version19::SerializedFile::read(&mut reader)?)
+                Box::new(version19::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::SupportsRefObject => {
-                Box::new(version20::SerializedFile::read(&mut reader)?)
+                Box::new(version20::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::StoresTypeDependencies => {
-                Box::new(version21::SerializedFile::read(&mut reader)?)
+                Box::new(version21::SerializedFile::read(reader)?)
             }
             SerializedFileFormatVersion::LargeFilesSupport => {
-                Box::new(version22::SerializedFile::read(&mut reader)?)
+                Box::new(version22::SerializedFile::read(reader)?)
             }
         };
+        Ok(file)
+    }
+
+    fn new(
+        content: Box<dyn Serialized + Send + Sync>,
+        object_data_source: ObjectDataSource,
+        serialized_file_id: i64,
+        resource_search_path: Option<String>,
+        type_tree_provider: Option<Arc<dyn TypeTreeProvider>>,
+    ) -> Self {
         let mut object_map = BTreeMap::new();
-        for obj in file.get_objects_metadata() {
+        for obj in content.get_objects_metadata() {
             object_map.insert(obj.path_id, obj);
         }
-        Ok(SerializedFile {
-            content: file,
-            file_reader: RefCell::new(reader),
+        SerializedFile {
+            content,
+            object_data_source,
             object_map,
+            object_data_overrides: BTreeMap::new(),
+            is_modified: false,
             serialized_file_id,
+            type_tree_provider,
             resource_search_path,
-        })
+        }
+    }
+
+    /// Add type trees of this file to its provider, so they are used for other files
+    /// of the same version without type tree. Type trees already in the provider are kept.
+    pub fn add_type_trees_to_provider(&self) {
+        let Some(type_tree_provider) = &self.type_tree_provider else {
+            return;
+        };
+        let version = self.get_unity_version();
+        if version.is_empty() {
+            return;
+        }
+        type_tree_provider.add_type_object_args(
+            &version,
+            self.collect_embedded_type_object_args(|class_id| {
+                !type_tree_provider.has_type_object_args(&version, class_id)
+            })
+            .into_values()
+            .collect(),
+        );
+    }
+
+    /// Check the header to find out whether the reader contain a serialized file.
+    /// The reader position is restored after check.
+    pub fn is_serialized_file<R: Read + Seek>(reader: &mut R) -> bool {
+        fn check<R: Read + Seek>(reader: &mut R, start_pos: u64) -> BinResult<bool> {
+            let total_size = reader.seek(SeekFrom::End(0))? - start_pos;
+            reader.seek(SeekFrom::Start(start_pos))?;
+            let head = SerializedFileCommonHeader::read(reader)?;
+            let (file_size, data_offset) =
+                if head.version == SerializedFileFormatVersion::LargeFilesSupport {
+                    reader.seek(SeekFrom::Start(start_pos + 24))?;
+                    (u64::read_be(reader)?, u64::read_be(reader)?)
+                } else {
+                    (head.file_size as u64, head.data_offset as u64)
+                };
+            Ok(file_size == total_size && data_offset <= file_size)
+        }
+
+        if let Ok(start_pos) = reader.stream_position() {
+            let is_serialized_file = check(reader, start_pos).unwrap_or(false);
+            if reader.seek(SeekFrom::Start(start_pos)).is_ok() {
+                return is_serialized_file;
+            }
+        }
+        false
     }
 
     pub fn get_object_map(&self) -> &BTreeMap<i64, Object> {
         &self.object_map
     }
 
+    /// Objects can be decoded from several threads at the same time.
+    /// Files read by [`Self::read_mmap`] slice the object data from the map without lock or copy.
+    /// Other files copy the whole object data out while the reader is locked,
+    /// so threads wait for each other's copy and the object is held twice in memory while it is decoded,
+    /// which matters for large objects such as textures and meshes.
     pub fn get_tt_object_by_path_id(&self, path_id: i64) -> Result<Option<TypeTreeObject>, Error> {
         self.object_map
             .get(&path_id)
             .map(|obj| {
-                self.content
-                    .get_type_tree_object(
-                        &mut self.file_reader.borrow_mut(),
-                        obj,
-                        self.serialized_file_id,
-                        path_id,
-                    )
+                self.read_object_data(obj)
+                    .map_err(Error::from)
+                    .and_then(|mut object_data_reader| {
+                        self.content.get_type_tree_object(
+                            &mut object_data_reader,
+                            obj,
+                            self.serialized_file_id,
+                            path_id,
+                            self.type_tree_provider.as_deref(),
+                        )
+                    })
                     .map_err(|err| Error::ObjectReadError {
                         source: err.into(),
                         data_offset: self.content.get_data_offset(),
@@ -457,14 +706,370 @@ impl SerializedFile {
         self.content.get_externals()
     }
 
+    pub fn get_unity_version(&self) -> String {
+        self.content.get_unity_version()
+    }
+
+    pub fn get_type_tree_provider(&self) -> Option<&Arc<dyn TypeTreeProvider>> {
+        self.type_tree_provider.as_ref()
+    }
+
+    /// Type trees stored in the file, keyed by class id, empty if the file has no type tree.
+    /// MonoBehaviour type trees are different for each script, so they are not included,
+    /// and managed reference types of this file are removed.
+    pub fn get_embedded_type_object_args(&self) -> BTreeMap<i32, TypeTreeObjectBinReadClassArgs> {
+        self.collect_embedded_type_object_args(|_| true)
+    }
+
+    fn collect_embedded_type_object_args<F: Fn(i32) -> bool>(
+        &self,
+        filter: F,
+    ) -> BTreeMap<i32, TypeTreeObjectBinReadClassArgs> {
+        let mut class_args_map = BTreeMap::new();
+        if !self.content.get_enable_type_tree() {
+            return class_args_map;
+        }
+        for obj in self.object_map.values() {
+            if obj.class < 0
+                || obj.class == 114
+                || class_args_map.contains_key(&obj.class)
+                || !filter(obj.class)
+            {
+                continue;
+            }
+            if let Some(class_args) = self.content.get_type_object_args_by_type_id(obj.type_id) {
+                class_args_map.insert(obj.class, class_args.with_ref_types(Vec::new()));
+            }
+        }
+        class_args_map
+    }
+
+    /// Script type table, each entry points to a MonoScript object of this file or an external file.
+    pub fn get_script_types(&self) -> Cow<Vec<ScriptType>> {
+        self.content.get_script_types()
+    }
+
+    pub fn get_type_info(&self, type_id: usize) -> Option<SerializedTypeInfo> {
+        self.content.get_type_info(type_id)
+    }
+
+    pub fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        self.content.get_type_infos()
+    }
+
+    pub fn get_header_info(&self) -> SerializedFileHeaderInfo {
+        SerializedFileHeaderInfo {
+            version: self.content.get_serialized_file_version().clone(),
+            metadata_size: self.content.get_metadata_size(),
+            file_size: self.content.get_file_size(),
+            data_offset: self.content.get_data_offset(),
+        }
+    }
+
+    pub fn get_metadata(&self) -> SerializedFileMetadata {
+        self.content.get_metadata(self.serialized_file_id)
+    }
+
+    pub fn get_user_information(&self) -> Option<String> {
+        self.content.get_user_information()
+    }
+
+    /// Summary of the file, read from metadata only, no object is decoded.
+    pub fn get_info(&self) -> SerializedFileInfo {
+        SerializedFileInfo {
+            header: self.get_header_info(),
+            metadata: self.get_metadata(),
+            user_information: self.get_user_information(),
+            types: self.get_type_infos(),
+            script_types: self.get_script_types().into_owned(),
+            externals: self
+                .get_externals()
+                .iter()
+                .map(ExternalFileInfo::from)
+                .collect(),
+            object_count: self.object_map.len(),
+        }
+    }
+
+    /// The script reference of a MonoBehaviour object, `None` for other objects.
+    pub fn get_object_script_type(&self, obj: &Object) -> Option<ScriptType> {
+        let script_types = self.get_script_types();
+        script_types.get(obj.get_script_type_index()?).cloned()
+    }
+
+    /// Replace the data of object, the type of object is not changed.
+    pub fn replace_object_data(&mut self, path_id: i64, data: Vec<u8>) -> Result<(), Error> {
+        let obj = self
+            .object_map
+            .get_mut(&path_id)
+            .ok_or(Error::ObjectNotFound(path_id))?;
+        obj.byte_size = data.len() as u32;
+        self.object_data_overrides.insert(path_id, data);
+        self.is_modified = true;
+        Ok(())
+    }
+
+    /// Replace the data of object with the data of `type_tree_object`,
+    /// which is usually read from this object and then edited.
+    pub fn replace_object(
+        &mut self,
+        path_id: i64,
+        type_tree_object: &TypeTreeObject,
+    ) -> Result<(), Error> {
+        self.replace_object_data(path_id, type_tree_object.get_object_data())
+    }
+
+    /// Add an object of the type `type_id` with a path id after all existing ones,
+    /// return the path id. If there is no positive path id after them,
+    /// the first unused positive path id is taken.
+    pub fn add_object(&mut self, type_id: usize, data: Vec<u8>) -> Result<i64, Error> {
+        let class = self
+            .content
+            .get_type_class_id(type_id)
+            .ok_or(Error::TypeNotFound(type_id))?;
+        let script_type_index = self
+            .content
+            .get_type_info(type_id)
+            .and_then(|info| info.script_type_index)
+            .map_or(-1, |index| index as i16);
+        let path_id = self
+            .object_map
+            .keys()
+            .next_back()
+            .map_or(Some(1), |path_id| path_id.max(&0).checked_add(1))
+            .unwrap_or_else(|| {
+                (1..)
+                    .find(|path_id| !self.object_map.contains_key(path_id))
+                    .unwrap_or_default()
+            });
+        self.object_map.insert(
+            path_id,
+            Object {
+                path_id,
+                byte_start: 0,
+                byte_size: data.len() as u32,
+                class,
+                type_id,
+                script_type_index,
+            },
+        );
+        self.object_data_overrides.insert(path_id, data);
+        self.is_modified = true;
+        Ok(path_id)
+    }
+
+    /// Add a type entry built from `type_fields`, return the type id for [`SerializedFile::add_object`].
+    /// Fields of a type read from another file can be get by [`TypeTreeObjectBinReadClassArgs::get_type_fields`].
+    pub fn add_type(
+        &mut self,
+        class_id: i32,
+        type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+    ) -> Result<usize, Error> {
+        let type_id = self.content.add_type(class_id, type_fields)?;
+        self.is_modified = true;
+        Ok(type_id)
+    }
+
+    pub fn remove_object(&mut self, path_id: i64) -> Result<(), Error> {
+        self.object_map
+            .remove(&path_id)
+            .ok_or(Error::ObjectNotFound(path_id))?;
+        self.object_data_overrides.remove(&path_id);
+        self.is_modified = true;
+        Ok(())
+    }
+
+    /// Write the file. Unmodified files are written with the layout they are read with,
+    /// objects are placed at their `byte_start` after the data offset and gaps are filled with zeros.
+    /// Once objects or types are edited, objects are laid out again in the order of path id,
+    /// every object aligned to 8 bytes.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        if self.is_modified {
+            return self.write_with_new_layout(writer);
+        }
+        let metadata = self.content.write_metadata()?;
+        writer.write_all(&metadata)?;
+        let mut position = metadata.len() as u64;
+        let data_offset = self.content.get_data_offset();
+        write_padding(writer, &mut position, data_offset)?;
+
+        let mut objects: Vec<&Object> = self.object_map.values().collect();
+        objects.sort_by_key(|obj| obj.byte_start);
+        for obj in objects {
+            let object_data_reader = self.read_object_data(obj)?;
+            write_padding(writer, &mut position, data_offset + obj.byte_start)?;
+            writer.write_all(object_data_reader.data.get_ref().as_ref())?;
+            position += obj.byte_size as u64;
+        }
+        write_padding(writer, &mut position, self.content.get_file_size())?;
+        Ok(())
+    }
+
+    fn write_with_new_layout<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut objects = Vec::with_capacity(self.object_map.len());
+        let mut data_size = 0;
+        for obj in self.object_map.values() {
+            let byte_start = u64::next_multiple_of(data_size, 8);
+            let byte_size = match self.object_data_overrides.get(&obj.path_id) {
+                Some(data) => data.len() as u32,
+                None => obj.byte_size,
+            };
+            data_size = byte_start + byte_size as u64;
+            objects.push(Object {
+                byte_start,
+                byte_size,
+                ..obj.clone()
+            });
+        }
+
+        // keep the data offset of file if the new metadata still fit in.
+        let metadata_size = self
+            .content
+            .write_metadata_with_layout(&objects, 0, 0)?
+            .len() as u64;
+        let data_offset = if metadata_size <= self.content.get_data_offset() {
+            self.content.get_data_offset()
+        } else {
+            metadata_size.next_multiple_of(16)
+        };
+        let metadata = self.content.write_metadata_with_layout(
+            &objects,
+            data_offset,
+            data_offset + data_size,
+        )?;
+        writer.write_all(&metadata)?;
+        let mut position = metadata.len() as u64;
+        write_padding(writer, &mut position, data_offset)?;
+        for (new_obj, obj) in objects.iter().zip(self.object_map.values()) {
+            let object_data_reader = self.read_object_data(obj)?;
+            write_padding(writer, &mut position, data_offset + new_obj.byte_start)?;
+            writer.write_all(object_data_reader.data.get_ref().as_ref())?;
+            position += new_obj.byte_size as u64;
+        }
+        Ok(())
+    }
+
+    fn read_object_data(&self, obj: &Object) -> std::io::Result<ObjectDataReader> {
+        // objects are read at their position in the file, so edited objects are placed there too.
+        let data_start = self.content.get_data_offset() + obj.byte_start;
+        if let Some(data) = self.object_data_overrides.get(&obj.path_id) {
+            return Ok(ObjectDataReader {
+                data_start,
+                data: Cursor::new(ObjectData::Owned(data.clone())),
+            });
+        }
+        let data = match &self.object_data_source {
+            ObjectDataSource::Reader(file_reader) => {
+                let mut data = vec![0u8; obj.byte_size as usize];
+                let mut file_reader = file_reader
+                    .lock()
+                    .map_err(|_| std::io::Error::from(ErrorKind::BrokenPipe))?;
+                file_reader.seek(SeekFrom::Start(data_start))?;
+                file_reader.read_exact(&mut data)?;
+                ObjectData::Owned(data)
+            }
+            #[cfg(feature = "mmap")]
+            ObjectDataSource::Mmap(mmap_resource) => {
+                let start = data_start as usize;
+                let end = start + obj.byte_size as usize;
+                if end > mmap_resource.get_data().len() {
+                    return Err(std::io::Error::from(ErrorKind::UnexpectedEof));
+                }
+                ObjectData::Mapped(mmap_resource.clone(), start..end)
+            }
+        };
+        Ok(ObjectDataReader {
+            data_start,
+            data: Cursor::new(data),
+        })
+    }
+
     pub fn get_serialized_file_id(&self) -> i64 {
         self.serialized_file_id
     }
 }
 
+/// Convert offset or size of the data layout for format versions which store them as u32.
+fn layout_value_to_u32(value: u64) -> BinResult<u32> {
+    u32::try_from(value).map_err(|_| {
+        binrw::Error::Io(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{value} is too large for serialized file version before 22"),
+        ))
+    })
+}
+
+fn unsupported_version_error(version: &SerializedFileFormatVersion) -> binrw::Error {
+    binrw::Error::Io(std::io::Error::new(
+        ErrorKind::Unsupported,
+        format!("can not write serialized file version {:?}", version),
+    ))
+}
+
+fn write_padding<W: Write>(writer: &mut W, position: &mut u64, end: u64) -> std::io::Result<()> {
+    if end < *position {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("data at {end} overlap data end at {position}"),
+        ));
+    }
+    std::io::copy(&mut std::io::repeat(0).take(end - *position), writer)?;
+    *position = end;
+    Ok(())
+}
+
+/// Data of one object which keep the position of the object in the serialized file,
+/// as fields are aligned by their position in the file.
+struct ObjectDataReader {
+    data_start: u64,
+    data: Cursor<ObjectData>,
+}
+
+enum ObjectData {
+    Owned(Vec<u8>),
+    /// Range of the mapped file, which is not copied.
+    #[cfg(feature = "mmap")]
+    Mapped(MmapResource, std::ops::Range<usize>),
+}
+
+impl AsRef<[u8]> for ObjectData {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            ObjectData::Owned(data) => data,
+            #[cfg(feature = "mmap")]
+            ObjectData::Mapped(mmap_resource, range) => &mmap_resource.get_data()[range.clone()],
+        }
+    }
+}
+
+impl Read for ObjectDataReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl Seek for ObjectDataReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::End(offset) => {
+                (self.data_start + self.data.get_ref().as_ref().len() as u64) as i64 + offset
+            }
+            SeekFrom::Current(offset) => (self.data_start + self.data.position()) as i64 + offset,
+        };
+        if new_pos < self.data_start as i64 {
+            return Err(std::io::Error::from(ErrorKind::InvalidInput));
+        }
+        self.data.set_position(new_pos as u64 - self.data_start);
+        Ok(new_pos as u64)
+    }
+}
+
 pub trait Serialized: fmt::Debug {
     fn get_serialized_file_version(&self) -> &SerializedFileFormatVersion;
     fn get_data_offset(&self) -> u64;
+    fn get_file_size(&self) -> u64;
+    fn get_metadata_size(&self) -> u64;
     fn get_endianess(&self) -> &Endian;
     fn get_objects_metadata(&self) -> Vec<Object>;
     fn get_type_object_args_by_type_id(
@@ -476,23 +1081,89 @@ pub trait Serialized: fmt::Debug {
     fn get_enable_type_tree(&self) -> bool;
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>>;
 
-    fn get_metadata(&self) -> SerializedFileMetadata {
+    /// Script types are stored since format version 11.
+    fn get_script_types(&self) -> Cow<Vec<ScriptType>> {
+        Cow::Owned(Vec::new())
+    }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo>;
+
+    /// Type entry of object, format versions before 16 refer to the type by class id.
+    fn get_type_info(&self, type_id: usize) -> Option<SerializedTypeInfo> {
+        let type_infos = self.get_type_infos();
+        if (self.get_serialized_file_version().clone() as u32)
+            < SerializedFileFormatVersion::RefactoredClassId as u32
+        {
+            type_infos
+                .into_iter()
+                .find(|info| info.class_id == type_id as i32)
+        } else {
+            type_infos.into_iter().nth(type_id)
+        }
+    }
+
+    /// User information is stored since format version 5.
+    fn get_user_information(&self) -> Option<String> {
+        None
+    }
+
+    /// Only implemented for the format versions which can be written.
+    fn get_type_class_id(&self, _type_id: usize) -> Option<i32> {
+        None
+    }
+
+    /// Header, type table, object table, script types, externals and ref types of the file,
+    /// without the object data. Only format versions from 17 to 22 can be written.
+    fn write_metadata(&self) -> BinResult<Vec<u8>> {
+        Err(unsupported_version_error(
+            self.get_serialized_file_version(),
+        ))
+    }
+
+    /// Same as [`Serialized::write_metadata`], but with the object table replaced by `objects`
+    /// and the header updated for the new data layout.
+    fn write_metadata_with_layout(
+        &self,
+        _objects: &[Object],
+        _data_offset: u64,
+        _file_size: u64,
+    ) -> BinResult<Vec<u8>> {
+        Err(unsupported_version_error(
+            self.get_serialized_file_version(),
+        ))
+    }
+
+    /// Append a type entry to the type table, return its type id.
+    fn add_type(
+        &mut self,
+        _class_id: i32,
+        _type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+    ) -> BinResult<usize> {
+        Err(unsupported_version_error(
+            self.get_serialized_file_version(),
+        ))
+    }
+
+    fn get_metadata(&self, serialized_file_id: i64) -> SerializedFileMetadata {
         SerializedFileMetadata {
             version: self.get_serialized_file_version().clone(),
             endianess: self.get_endianess().clone(),
-            unity_version: UnityVersion::from_str(&self.get_unity_version()).unwrap(),
+            // format versions before 5 do not store the unity version.
+            unity_version: UnityVersion::from_str(&self.get_unity_version())
+                .unwrap_or_else(|_| UnityVersion::new(Vec::new(), None)),
             target_platform: self.get_target_platform().clone(),
             enable_type_tree: self.get_enable_type_tree(),
-            serialized_file_id: 0,
+            serialized_file_id,
         }
     }
 
     fn get_type_tree_object(
         &self,
-        reader: &mut Box<dyn UnityResource + Send + Sync>,
+        mut reader: &mut dyn UnityResource,
         obj: &Object,
         serialized_file_id: i64,
         path_id: i64,
+        type_tree_provider: Option<&dyn TypeTreeProvider>,
     ) -> Result<TypeTreeObject, Error> {
         let class_args = if self.get_enable_type_tree() {
             self.get_type_object_args_by_type_id(obj.type_id)
@@ -500,11 +1171,9 @@ pub trait Serialized: fmt::Debug {
             None
         };
 
-        #[cfg(feature = "type-tree-json")]
-        let class_args = class_args.or(get_type_object_args_by_version_class_id(
-            &self.get_unity_version(),
-            obj.class,
-        ));
+        let class_args = class_args.or_else(|| {
+            type_tree_provider?.get_type_object_args(&self.get_unity_version(), obj.class)
+        });
 
         let class_args = class_args.ok_or(Error::TypeTreeObjectBinReadArgsBuild)?;
 
@@ -513,7 +1182,7 @@ pub trait Serialized: fmt::Debug {
         reader.seek(SeekFrom::Start(self.get_data_offset() + obj.byte_start))?;
 
         let mut type_tree_object =
-            TypeTreeObject::read_options(reader, self.get_endianess().into(), args)?;
+            TypeTreeObject::read_options(&mut reader, self.get_endianess().into(), args)?;
         let apos = reader.stream_position()?;
         if apos - (self.get_data_offset() + obj.byte_start) != obj.byte_size as u64 {
             let mut external_data = vec![
@@ -527,3 +1196,712 @@ pub trait Serialized: fmt::Debug {
         Ok(type_tree_object)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::type_tree::convert::TryCastFrom;
+    use crate::type_tree::provider::MemoryTypeTreeProvider;
+
+    /// Build a little endian serialized file of `version` (15 to 22) with one type `TestClass { int m_Value }`
+    /// and an object of it for every value in `values`.
+    pub(crate) fn build_serialized_file(version: u32, values: &[i32]) -> Vec<u8> {
+        build_serialized_file_with_type_tree(version, values, true)
+    }
+
+    /// Header of a version 17 serialized file followed by zeros up to `file_size`,
+    /// which is only a serialized file for [`SerializedFile::is_serialized_file`].
+    pub(crate) fn build_serialized_file_header(file_size: u32, data_offset: u32) -> Vec<u8> {
+        let mut file = Vec::new();
+        file.extend(0u32.to_be_bytes());
+        file.extend(file_size.to_be_bytes());
+        file.extend(17u32.to_be_bytes());
+        file.extend(data_offset.to_be_bytes());
+        file.resize(file_size as usize, 0);
+        file
+    }
+
+    pub(crate) fn build_serialized_file_with_type_tree(
+        version: u32,
+        values: &[i32],
+        enable_type_tree: bool,
+    ) -> Vec<u8> {
+        let mut metadata = Vec::new();
+        metadata.extend(b"2020.3.0f1\0");
+        metadata.extend(19i32.to_le_bytes()); // StandaloneWindows64
+        metadata.push(enable_type_tree as u8);
+
+        let string_buffer = b"TestClass\0Base\0int\0m_Value\0";
+        let nodes: [(u8, u32, u32); 2] = [(0, 0, 10), (1, 15, 19)];
+        let type_tree = |metadata: &mut Vec<u8>| {
+            metadata.extend((nodes.len() as i32).to_le_bytes());
+            metadata.extend((string_buffer.len() as i32).to_le_bytes());
+            for (index, (level, type_str_offset, name_str_offset)) in nodes.iter().enumerate() {
+                metadata.extend(1u16.to_le_bytes());
+                metadata.push(*level);
+                metadata.push(0);
+                metadata.extend(type_str_offset.to_le_bytes());
+                metadata.extend(name_str_offset.to_le_bytes());
+                metadata.extend(4i32.to_le_bytes());
+                metadata.extend((index as i32).to_le_bytes());
+                metadata.extend(0i32.to_le_bytes());
+                if version >= 19 {
+                    metadata.extend(0u64.to_le_bytes());
+                }
+            }
+            metadata.extend(string_buffer);
+        };
+        let serialized_type = |metadata: &mut Vec<u8>| {
+            metadata.extend(1000i32.to_le_bytes());
+            if version >= 16 {
+                metadata.push(0);
+            }
+            if version >= 17 {
+                metadata.extend(0i16.to_le_bytes());
+            }
+            metadata.extend([0xabu8; 16]);
+            if enable_type_tree {
+                type_tree(metadata);
+            }
+        };
+
+        metadata.extend(1u32.to_le_bytes());
+        serialized_type(&mut metadata);
+        if version >= 21 {
+            metadata.extend(0i32.to_le_bytes());
+        }
+
+        let header_size: usize = if version >= 22 { 48 } else { 20 };
+        metadata.extend((values.len() as i32).to_le_bytes());
+        for (i, _) in values.iter().enumerate() {
+            metadata.resize(
+                (header_size + metadata.len()).next_multiple_of(4) - header_size,
+                0,
+            );
+            metadata.extend((i as i64 + 1).to_le_bytes());
+            if version >= 22 {
+                metadata.extend((i as u64 * 8).to_le_bytes());
+            } else {
+                metadata.extend((i as u32 * 8).to_le_bytes());
+            }
+            metadata.extend(4u32.to_le_bytes());
+            if version >= 16 {
+                metadata.extend(0i32.to_le_bytes());
+            } else {
+                // objects refer to types by class id before version 16.
+                metadata.extend(1000i32.to_le_bytes());
+                metadata.extend(1000u16.to_le_bytes());
+            }
+            if version <= 16 {
+                metadata.extend(0i16.to_le_bytes());
+                metadata.push(0);
+            }
+        }
+
+        metadata.extend(1i32.to_le_bytes());
+        metadata.extend(0i32.to_le_bytes());
+        metadata.resize(
+            (header_size + metadata.len()).next_multiple_of(4) - header_size,
+            0,
+        );
+        metadata.extend(1i64.to_le_bytes());
+
+        metadata.extend(1i32.to_le_bytes());
+        metadata.push(0);
+        metadata.extend([0x12u8; 16]);
+        metadata.extend(0i32.to_le_bytes());
+        metadata.extend(b"library/unity default resources\0");
+
+        if version >= 20 {
+            metadata.extend(1i32.to_le_bytes());
+            serialized_type(&mut metadata);
+            if version >= 21 {
+                metadata.extend(b"TestRef\0Test\0Assembly-CSharp\0");
+            }
+        }
+        metadata.extend(b"\0");
+
+        let data_offset = (header_size + metadata.len()).div_ceil(16) as u64 * 16;
+        let file_size = data_offset + values.len() as u64 * 8;
+        let mut file = Vec::new();
+        if version >= 22 {
+            file.extend([0u8; 8]);
+            file.extend(version.to_be_bytes());
+            file.extend([0u8; 4]);
+            file.extend([0u8; 4]);
+            file.extend((metadata.len() as u32).to_be_bytes());
+            file.extend(file_size.to_be_bytes());
+            file.extend(data_offset.to_be_bytes());
+            file.extend(0u64.to_be_bytes());
+        } else {
+            file.extend((metadata.len() as u32).to_be_bytes());
+            file.extend((file_size as u32).to_be_bytes());
+            file.extend(version.to_be_bytes());
+            file.extend((data_offset as u32).to_be_bytes());
+            file.extend([0u8; 4]);
+        }
+        file.extend(metadata);
+        file.resize(data_offset as usize, 0);
+        for value in values {
+            file.extend(value.to_le_bytes());
+            file.extend([0u8; 4]);
+        }
+        file
+    }
+
+    #[test]
+    fn test_is_serialized_file() {
+        assert!(SerializedFile::is_serialized_file(&mut Cursor::new(
+            build_serialized_file_header(100, 40)
+        )));
+        // the size in header must be the size of data.
+        let mut truncated = build_serialized_file_header(100, 40);
+        truncated.truncate(90);
+        assert!(!SerializedFile::is_serialized_file(&mut Cursor::new(
+            truncated
+        )));
+        let mut with_trailing_data = build_serialized_file_header(100, 40);
+        with_trailing_data.extend([0u8; 20]);
+        assert!(!SerializedFile::is_serialized_file(&mut Cursor::new(
+            with_trailing_data
+        )));
+        assert!(!SerializedFile::is_serialized_file(&mut Cursor::new(
+            build_serialized_file_header(100, 200)
+        )));
+        assert!(!SerializedFile::is_serialized_file(&mut Cursor::new(
+            vec![0u8; 8]
+        )));
+
+        // checked from the reader position, which is restored.
+        let mut data = vec![0xffu8; 10];
+        data.extend(build_serialized_file_header(100, 40));
+        let mut reader = Cursor::new(data);
+        reader.set_position(10);
+        assert!(SerializedFile::is_serialized_file(&mut reader));
+        assert_eq!(reader.position(), 10);
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        for version in 17..=22 {
+            let data = build_serialized_file(version, &[7, -3]);
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data.clone())), 0, None).unwrap();
+            assert!(SerializedFile::is_serialized_file(&mut Cursor::new(&data)));
+            let obj = serialized_file
+                .get_tt_object_by_path_id(2)
+                .unwrap()
+                .unwrap();
+            assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), -3);
+
+            let mut written = Vec::new();
+            serialized_file.write(&mut written).unwrap();
+            assert_eq!(written, data, "version {version}");
+        }
+    }
+
+    #[test]
+    fn test_write_keep_data_offset() {
+        for version in [17, 22] {
+            // move the data to a page aligned offset as unity does for large files.
+            let data = build_serialized_file(version, &[7, -3]);
+            let data_offset = SerializedFile::read(Box::new(Cursor::new(data.clone())), 0, None)
+                .unwrap()
+                .content
+                .get_data_offset() as usize;
+            let mut moved = data[..data_offset].to_vec();
+            moved.resize(4096, 0);
+            moved.extend(&data[data_offset..]);
+            let file_size = moved.len();
+            if version >= 22 {
+                moved[24..32].copy_from_slice(&(file_size as u64).to_be_bytes());
+                moved[32..40].copy_from_slice(&4096u64.to_be_bytes());
+            } else {
+                moved[4..8].copy_from_slice(&(file_size as u32).to_be_bytes());
+                moved[12..16].copy_from_slice(&4096u32.to_be_bytes());
+            }
+
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(moved.clone())), 0, None).unwrap();
+            let obj = serialized_file
+                .get_tt_object_by_path_id(2)
+                .unwrap()
+                .unwrap();
+            assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), -3);
+            let mut written = Vec::new();
+            serialized_file.write(&mut written).unwrap();
+            assert_eq!(written, moved, "version {version}");
+        }
+    }
+
+    #[test]
+    fn test_write_unsupported_version() {
+        for version in [15, 16] {
+            let data = build_serialized_file(version, &[7]);
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+            let mut written = Vec::new();
+            assert!(serialized_file.write(&mut written).is_err());
+            assert!(written.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_script_types() {
+        for version in 17..=22 {
+            let data = build_serialized_file(version, &[7, -3]);
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+            let script_type = ScriptType {
+                local_serialized_file_index: 0,
+                local_identifier_in_file: 1,
+            };
+            assert_eq!(
+                *serialized_file.get_script_types(),
+                vec![script_type.clone()]
+            );
+            assert_eq!(
+                serialized_file.get_type_info(0),
+                Some(SerializedTypeInfo {
+                    class_id: 1000,
+                    script_type_index: Some(0),
+                    is_stripped_type: Some(false),
+                    script_id: None,
+                    old_type_hash: Some([0xab; 16]),
+                    type_tree_node_count: 2,
+                })
+            );
+            assert_eq!(serialized_file.get_type_info(1), None);
+            let obj = serialized_file.get_object_map().get(&2).unwrap();
+            assert_eq!(obj.get_script_type_index(), Some(0));
+            assert_eq!(
+                serialized_file.get_object_script_type(obj),
+                Some(script_type)
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_info_by_class_id() {
+        let data = build_serialized_file(15, &[7, -3]);
+        let serialized_file = SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+        let obj = serialized_file.get_object_map().get(&2).unwrap();
+        assert_eq!(obj.type_id, 1000);
+        assert_eq!(
+            serialized_file
+                .get_type_info(obj.type_id)
+                .map(|info| info.class_id),
+            Some(1000)
+        );
+        assert_eq!(serialized_file.get_type_info(0), None);
+        assert_eq!(
+            serialized_file.get_object_script_type(obj),
+            Some(ScriptType {
+                local_serialized_file_index: 0,
+                local_identifier_in_file: 1,
+            })
+        );
+        let obj = serialized_file
+            .get_tt_object_by_path_id(2)
+            .unwrap()
+            .unwrap();
+        assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), -3);
+    }
+
+    #[test]
+    fn test_script_type_out_of_range() {
+        for version in [15, 16, 22] {
+            let data = build_serialized_file(version, &[7, -3]);
+            let mut serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+            for script_type_index in [-1, 1, i16::MAX] {
+                let obj = serialized_file.object_map.get_mut(&2).unwrap();
+                obj.script_type_index = script_type_index;
+                let obj = serialized_file.get_object_map().get(&2).unwrap();
+                assert_eq!(
+                    serialized_file.get_object_script_type(obj),
+                    None,
+                    "version {version} index {script_type_index}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_info_by_type_id_from_v16() {
+        let data = build_serialized_file(16, &[7]);
+        let serialized_file = SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+        let obj = serialized_file.get_object_map().get(&1).unwrap();
+        assert_eq!(obj.type_id, 0);
+        assert_eq!(
+            serialized_file
+                .get_type_info(obj.type_id)
+                .map(|info| info.class_id),
+            Some(1000)
+        );
+        assert_eq!(serialized_file.get_type_info(1000), None);
+    }
+
+    #[test]
+    fn test_ref_types() {
+        for (version, class_name, namespace) in [
+            (20, "TestClass", ""),
+            (21, "TestRef", "Test"),
+            (22, "TestRef", "Test"),
+        ] {
+            let data = build_serialized_file(version, &[7]);
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+            let class_args = serialized_file
+                .content
+                .get_type_object_args_by_type_id(0)
+                .unwrap();
+            let ref_types = class_args.get_ref_types();
+            assert_eq!(ref_types.len(), 1, "version {version}");
+            assert_eq!(ref_types[0].get_class_name(), class_name);
+            assert_eq!(ref_types[0].get_namespace(), namespace);
+            assert_eq!(ref_types[0].get_type_fields().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_get_info() {
+        for version in 15..=22 {
+            let data = build_serialized_file(version, &[7, -3]);
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data.clone())), 3, None).unwrap();
+            let info = serialized_file.get_info();
+            assert_eq!(info.header.version.clone() as u32, version);
+            assert_eq!(info.header.file_size, data.len() as u64);
+            let header_size = if version >= 22 { 48 } else { 20 };
+            assert_eq!(
+                info.header.data_offset,
+                (header_size + info.header.metadata_size).next_multiple_of(16)
+            );
+            assert_eq!(info.metadata.serialized_file_id, 3);
+            assert_eq!(info.metadata.endianess, Endian::Little);
+            assert_eq!(
+                info.metadata.unity_version,
+                UnityVersion::from_str("2020.3.0f1").unwrap()
+            );
+            assert_eq!(
+                info.metadata.target_platform,
+                BuildTarget::StandaloneWindows64
+            );
+            assert!(info.metadata.enable_type_tree);
+            assert_eq!(info.user_information, Some(String::new()));
+            assert_eq!(info.types.len(), 1);
+            assert_eq!(info.types[0].class_id, 1000);
+            assert_eq!(info.types[0].type_tree_node_count, 2);
+            assert_eq!(info.types[0].old_type_hash, Some([0xab; 16]));
+            assert_eq!(
+                info.types[0].is_stripped_type,
+                (version >= 16).then_some(false)
+            );
+            assert_eq!(info.script_types.len(), 1);
+            assert_eq!(
+                info.externals,
+                vec![ExternalFileInfo {
+                    path: "library/unity default resources".to_owned(),
+                    guid: [0x12; 16],
+                    r#type: 0,
+                }]
+            );
+            assert_eq!(info.object_count, 2);
+            #[cfg(feature = "type-tree-json")]
+            {
+                let json = serde_json::to_value(&info).unwrap();
+                assert_eq!(json["metadata"]["target_platform"], "StandaloneWindows64");
+                assert_eq!(json["types"][0]["class_id"], 1000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_info_without_type_tree() {
+        for version in 15..=22 {
+            let data = build_serialized_file_with_type_tree(version, &[7, -3], false);
+            let mut serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 5, None).unwrap();
+            let info = serialized_file.get_info();
+            assert_eq!(info.metadata.serialized_file_id, 5);
+            assert!(!info.metadata.enable_type_tree);
+            assert_eq!(info.types.len(), 1);
+            assert_eq!(info.types[0].class_id, 1000);
+            assert_eq!(info.types[0].type_tree_node_count, 0);
+
+            // the object count follows edits before the file is written.
+            serialized_file.remove_object(1).unwrap();
+            assert_eq!(serialized_file.get_info().object_count, 1);
+        }
+    }
+
+    #[test]
+    fn test_edit_objects() {
+        for version in 17..=22 {
+            let data = build_serialized_file(version, &[7, -3, 5]);
+            let mut serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+
+            let mut obj = serialized_file
+                .get_tt_object_by_path_id(1)
+                .unwrap()
+                .unwrap();
+            obj.try_as_mut_slice("/Base/m_Value")
+                .unwrap()
+                .copy_from_slice(&42i32.to_le_bytes());
+            serialized_file.replace_object(1, &obj).unwrap();
+            serialized_file.remove_object(2).unwrap();
+            assert!(serialized_file.remove_object(2).is_err());
+
+            let type_fields = serialized_file
+                .content
+                .get_type_object_args_by_type_id(0)
+                .unwrap()
+                .get_type_fields()
+                .clone();
+            let type_id = serialized_file.add_type(1001, &type_fields).unwrap();
+            assert_eq!(type_id, 1);
+            let path_id = serialized_file
+                .add_object(type_id, 123i32.to_le_bytes().to_vec())
+                .unwrap();
+            assert_eq!(path_id, 4);
+            assert!(serialized_file.add_object(9, Vec::new()).is_err());
+
+            // edited objects can be read before the file is written.
+            for (path_id, value) in [(1, 42), (4, 123)] {
+                let obj = serialized_file
+                    .get_tt_object_by_path_id(path_id)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), value);
+                assert!(obj.external_data.is_none());
+            }
+
+            let mut written = Vec::new();
+            serialized_file.write(&mut written).unwrap();
+            assert!(SerializedFile::is_serialized_file(&mut Cursor::new(
+                &written
+            )));
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(written.clone())), 0, None).unwrap();
+            let values: Vec<(i64, i32, i32)> = serialized_file
+                .get_object_map()
+                .keys()
+                .map(|path_id| {
+                    let obj = serialized_file
+                        .get_tt_object_by_path_id(*path_id)
+                        .unwrap()
+                        .unwrap();
+                    (
+                        *path_id,
+                        obj.class_id,
+                        i32::try_cast_from(&obj, "/Base/m_Value").unwrap(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                values,
+                vec![(1, 1000, 42), (3, 1000, 5), (4, 1001, 123)],
+                "version {version}"
+            );
+
+            let mut rewritten = Vec::new();
+            serialized_file.write(&mut rewritten).unwrap();
+            assert_eq!(rewritten, written, "version {version}");
+        }
+    }
+
+    #[test]
+    fn test_add_object_path_id() {
+        let data = build_serialized_file(22, &[7]);
+        let mut serialized_file =
+            SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+        let obj = serialized_file.object_map[&1].clone();
+        serialized_file.remove_object(1).unwrap();
+        assert_eq!(serialized_file.add_object(0, Vec::new()).unwrap(), 1);
+
+        // negative path ids are never counted from.
+        let mut insert_object = |path_id| {
+            serialized_file.object_map.insert(
+                path_id,
+                Object {
+                    path_id,
+                    ..obj.clone()
+                },
+            );
+        };
+        insert_object(-5);
+        insert_object(i64::MAX);
+        let path_id = serialized_file.add_object(0, Vec::new()).unwrap();
+        assert_eq!(path_id, 2);
+        let path_id = serialized_file.add_object(0, Vec::new()).unwrap();
+        assert_eq!(path_id, 3);
+
+        let data = build_serialized_file(22, &[]);
+        let mut serialized_file =
+            SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+        serialized_file
+            .object_map
+            .insert(-5, Object { path_id: -5, ..obj });
+        assert_eq!(serialized_file.add_object(0, Vec::new()).unwrap(), 1);
+        assert_eq!(serialized_file.object_map.len(), 2);
+    }
+
+    #[test]
+    fn test_write_new_layout_alignment() {
+        for version in 17..=22 {
+            let data = build_serialized_file(version, &[7, -3]);
+            let mut serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(data)), 0, None).unwrap();
+            let objects_data = [vec![1u8, 2, 3], vec![4u8; 9], vec![5u8; 5]];
+            serialized_file
+                .replace_object_data(1, objects_data[0].clone())
+                .unwrap();
+            serialized_file
+                .replace_object_data(2, objects_data[1].clone())
+                .unwrap();
+            serialized_file
+                .add_object(0, objects_data[2].clone())
+                .unwrap();
+
+            let mut written = Vec::new();
+            serialized_file.write(&mut written).unwrap();
+            let serialized_file =
+                SerializedFile::read(Box::new(Cursor::new(written.clone())), 0, None).unwrap();
+            let data_offset = serialized_file.content.get_data_offset() as usize;
+            assert_eq!(
+                serialized_file.content.get_file_size() as usize,
+                written.len()
+            );
+            let mut data_end = data_offset;
+            for (obj, object_data) in serialized_file.get_object_map().values().zip(&objects_data) {
+                let start = data_offset + obj.byte_start as usize;
+                assert_eq!(obj.byte_start % 8, 0, "version {version}");
+                assert!(written[data_end..start].iter().all(|b| *b == 0));
+                data_end = start + obj.byte_size as usize;
+                assert_eq!(&written[start..data_end], object_data.as_slice());
+            }
+            assert_eq!(data_end, written.len(), "version {version}");
+        }
+    }
+
+    #[test]
+    fn test_object_data_reader() {
+        let mut reader = ObjectDataReader {
+            data_start: 100,
+            data: Cursor::new(ObjectData::Owned((0..20u8).collect())),
+        };
+        let mut buff = [0u8; 4];
+        assert_eq!(reader.seek(SeekFrom::Start(110)).unwrap(), 110);
+        reader.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, [10, 11, 12, 13]);
+        assert_eq!(reader.stream_position().unwrap(), 114);
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 118);
+        assert!(reader.seek(SeekFrom::Start(99)).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_read_mmap() {
+        let data = build_serialized_file(22, &[7, -3]);
+        let path = std::env::temp_dir().join(format!("io_unity_mmap_cab_{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let serialized_file =
+            SerializedFile::read_mmap(MmapResource::open(&path).unwrap(), 0, None, None).unwrap();
+
+        // object data is sliced from the map.
+        let object_data_reader = serialized_file
+            .read_object_data(&serialized_file.get_object_map()[&1])
+            .unwrap();
+        assert!(matches!(
+            object_data_reader.data.get_ref(),
+            ObjectData::Mapped(..)
+        ));
+        std::thread::scope(|scope| {
+            for (path_id, value) in [(1, 7), (2, -3), (1, 7), (2, -3)] {
+                let serialized_file = &serialized_file;
+                scope.spawn(move || {
+                    let obj = serialized_file
+                        .get_tt_object_by_path_id(path_id)
+                        .unwrap()
+                        .unwrap();
+                    assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), value);
+                });
+            }
+        });
+        let mut written = Vec::new();
+        serialized_file.write(&mut written).unwrap();
+        assert_eq!(written, data);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_type_tree_provider() {
+        for version in 17..=22 {
+            let provider = Arc::new(MemoryTypeTreeProvider::new());
+            let stripped = build_serialized_file_with_type_tree(version, &[7], false);
+            let serialized_file = SerializedFile::read_with_type_tree_provider(
+                Box::new(Cursor::new(stripped.clone())),
+                0,
+                None,
+                Some(provider.clone()),
+            )
+            .unwrap();
+            assert!(serialized_file.get_tt_object_by_path_id(1).is_err());
+
+            // type trees are only added to the provider on request
+            let with_type_tree = SerializedFile::read_with_type_tree_provider(
+                Box::new(Cursor::new(build_serialized_file(version, &[9]))),
+                1,
+                None,
+                Some(provider.clone()),
+            )
+            .unwrap();
+            assert!(!provider.has_type_object_args("2020.3.0f1", 1000));
+            with_type_tree.add_type_trees_to_provider();
+            assert!(provider.has_type_object_args("2020.3.0f1", 1000));
+            let class_args = provider.get_type_object_args("2020.3.0f1", 1000).unwrap();
+            assert!(class_args.get_ref_types().is_empty());
+            let obj = serialized_file
+                .get_tt_object_by_path_id(1)
+                .unwrap()
+                .unwrap();
+            assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), 7);
+
+            // plain read use the default provider, which can be opted out by `None`
+            let plain =
+                SerializedFile::read(Box::new(Cursor::new(stripped.clone())), 3, None).unwrap();
+            assert_eq!(
+                plain.get_type_tree_provider().is_some(),
+                cfg!(feature = "type-tree-json")
+            );
+            let without_provider = SerializedFile::read_with_type_tree_provider(
+                Box::new(Cursor::new(stripped.clone())),
+                4,
+                None,
+                None,
+            )
+            .unwrap();
+            assert!(without_provider.get_type_tree_provider().is_none());
+            assert!(without_provider.get_tt_object_by_path_id(1).is_err());
+
+            // other providers are not affected
+            let serialized_file = SerializedFile::read_with_type_tree_provider(
+                Box::new(Cursor::new(stripped)),
+                2,
+                None,
+                Some(Arc::new(MemoryTypeTreeProvider::new())),
+            )
+            .unwrap();
+            assert!(serialized_file.get_tt_object_by_path_id(1).is_err());
+        }
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SerializedFile>();
+        assert_send_sync::<crate::unity_asset_view::UnityAssetViewer>();
+    }
+}
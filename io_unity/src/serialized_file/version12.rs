@@ -1,7 +1,9 @@
 use super::version13::{Object, ObjectBinReadArgs, ScriptType};
 use super::version17::{FileIdentifier, TypeTree, TypeTreeNode};
+use super::{
+    version17, Serialized, SerializedFileFormatVersion, SerializedTypeEntry, SerializedTypeInfo,
+};
 use super::{BuildTarget, SerializedFileCommonHeader};
-use super::{Serialized, SerializedFileFormatVersion};
 use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::until::Endian;
 use binrw::io::Cursor;
@@ -29,6 +31,14 @@ impl Serialized for SerializedFile {
         self.header.data_offset as u64
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header.file_size as u64
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -43,6 +53,7 @@ impl Serialized for SerializedFile {
                 byte_size: obj.byte_size,
                 class: obj.class_id as i32,
                 type_id: obj.type_id as usize,
+                script_type_index: obj.script_type_index,
             })
             .collect()
     }
@@ -88,6 +99,24 @@ impl Serialized for SerializedFile {
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>> {
         return Cow::Borrowed(&self.content.externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
+
+    fn get_user_information(&self) -> Option<String> {
+        Some(self.content.user_information.to_string())
+    }
+
+    fn get_script_types(&self) -> Cow<Vec<version17::ScriptType>> {
+        Cow::Owned(
+            self.content
+                .script_types
+                .iter()
+                .map(version17::ScriptType::from)
+                .collect(),
+        )
+    }
 }
 
 #[binrw]
@@ -117,3 +146,13 @@ pub struct SerializedType {
     pub class_id: i32,
     pub type_tree: TypeTree,
 }
+
+impl SerializedTypeEntry for SerializedType {
+    fn get_class_id(&self) -> i32 {
+        self.class_id
+    }
+
+    fn get_type_tree_node_count(&self) -> usize {
+        self.type_tree.type_tree_node_blobs.len()
+    }
+}
@@ -1,7 +1,7 @@
 use super::version15::{SerializedType, SerializedTypeBinReadArgs};
 use super::version17::{FileIdentifier, TypeTreeNode};
+use super::{version17, Serialized, SerializedFileFormatVersion, SerializedTypeInfo};
 use super::{BuildTarget, SerializedFileCommonHeader};
-use super::{Serialized, SerializedFileFormatVersion};
 use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::until::{binrw_parser::*, Endian};
 use binrw::{binrw, BinResult, NullString};
@@ -29,6 +29,14 @@ impl Serialized for SerializedFile {
         self.header.data_offset as u64
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header.file_size as u64
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -43,6 +51,7 @@ impl Serialized for SerializedFile {
                 byte_size: obj.byte_size,
                 class: obj.class_id as i32,
                 type_id: obj.type_id as usize,
+                script_type_index: obj.script_type_index,
             })
             .collect()
     }
@@ -88,6 +97,24 @@ impl Serialized for SerializedFile {
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>> {
         return Cow::Borrowed(&self.content.externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
+
+    fn get_user_information(&self) -> Option<String> {
+        Some(self.content.user_information.to_string())
+    }
+
+    fn get_script_types(&self) -> Cow<Vec<version17::ScriptType>> {
+        Cow::Owned(
+            self.content
+                .script_types
+                .iter()
+                .map(version17::ScriptType::from)
+                .collect(),
+        )
+    }
 }
 
 #[binrw]
@@ -132,6 +159,15 @@ pub struct ScriptType {
     local_identifier_in_file: i32,
 }
 
+impl From<&ScriptType> for version17::ScriptType {
+    fn from(script_type: &ScriptType) -> Self {
+        version17::ScriptType {
+            local_serialized_file_index: script_type.local_serialized_file_index,
+            local_identifier_in_file: script_type.local_identifier_in_file as i64,
+        }
+    }
+}
+
 #[binrw::parser(reader, endian)]
 pub fn path_id_parser(flags: (bool,)) -> BinResult<i64> {
     let (big_id_enabled,) = flags;
@@ -1,6 +1,6 @@
 use super::version17::{FileIdentifier, ScriptType, TypeTree, TypeTreeNode};
 use super::{BuildTarget, SerializedFileCommonHeader};
-use super::{Serialized, SerializedFileFormatVersion};
+use super::{Serialized, SerializedFileFormatVersion, SerializedTypeEntry, SerializedTypeInfo};
 use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::until::{binrw_parser::*, Endian};
 use binrw::io::Cursor;
@@ -28,6 +28,14 @@ impl Serialized for SerializedFile {
         self.header.data_offset as u64
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header.file_size as u64
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -47,6 +55,7 @@ impl Serialized for SerializedFile {
                     .map(|t| t.class_id)
                     .unwrap_or(0),
                 type_id: obj.type_id as usize,
+                script_type_index: obj.script_type_index,
             })
             .collect()
     }
@@ -88,6 +97,18 @@ impl Serialized for SerializedFile {
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>> {
         return Cow::Borrowed(&self.content.externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
+
+    fn get_user_information(&self) -> Option<String> {
+        Some(self.content.user_information.to_string())
+    }
+
+    fn get_script_types(&self) -> Cow<Vec<ScriptType>> {
+        Cow::Borrowed(&self.content.script_types)
+    }
 }
 
 #[binrw]
@@ -124,6 +145,30 @@ pub struct SerializedType {
     type_tree: Option<TypeTree>,
 }
 
+impl SerializedTypeEntry for SerializedType {
+    fn get_class_id(&self) -> i32 {
+        self.class_id
+    }
+
+    fn get_type_tree_node_count(&self) -> usize {
+        self.type_tree
+            .as_ref()
+            .map_or(0, |type_tree| type_tree.type_tree_node_blobs.len())
+    }
+
+    fn is_stripped_type(&self) -> Option<bool> {
+        Some(*self.is_stripped_type)
+    }
+
+    fn get_script_id(&self) -> Option<[u8; 16]> {
+        self.script_id
+    }
+
+    fn get_old_type_hash(&self) -> Option<[u8; 16]> {
+        Some(self.old_type_hash)
+    }
+}
+
 #[binrw]
 #[derive(Debug, PartialEq)]
 pub struct Object {
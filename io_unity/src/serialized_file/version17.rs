@@ -1,23 +1,25 @@
 use std::borrow::Cow;
 
-use super::{BuildTarget, SerializedFileCommonHeader, COMMON_STRING};
-use super::{Serialized, SerializedFileFormatVersion};
+use super::{layout_value_to_u32, BuildTarget, SerializedFileCommonHeader, COMMON_STRING};
+use super::{Serialized, SerializedFileFormatVersion, SerializedTypeEntry, SerializedTypeInfo};
 use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::until::{binrw_parser::*, Endian};
-use binrw::{binrw, NullString};
+use binrw::{binrw, BinResult, BinWrite, NullString};
 use binrw::{io::Cursor, BinRead};
+use std::collections::HashMap;
 use std::fmt;
 use std::io::{prelude::*, SeekFrom};
 use std::sync::Arc;
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SerializedFile {
     header: SerializedFileCommonHeader,
     endianess: Endian,
     reserved: [u8; 3],
     #[br(is_little = endianess == Endian::Little)]
+    #[bw(is_little = *endianess == Endian::Little)]
     content: SerializedFileContent,
 }
 
@@ -30,6 +32,14 @@ impl Serialized for SerializedFile {
         self.header.data_offset as u64
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header.file_size as u64
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -49,6 +59,12 @@ impl Serialized for SerializedFile {
                     .map(|t| t.class_id)
                     .unwrap_or(0),
                 type_id: obj.type_id as usize,
+                script_type_index: self
+                    .content
+                    .types
+                    .get(obj.type_id as usize)
+                    .map(|t| t.script_type_index)
+                    .unwrap_or(-1),
             })
             .collect()
     }
@@ -90,10 +106,77 @@ impl Serialized for SerializedFile {
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>> {
         return Cow::Borrowed(&self.content.externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
+
+    fn get_user_information(&self) -> Option<String> {
+        Some(self.content.user_information.to_string())
+    }
+
+    fn get_script_types(&self) -> Cow<Vec<ScriptType>> {
+        Cow::Borrowed(&self.content.script_types)
+    }
+
+    fn get_type_class_id(&self, type_id: usize) -> Option<i32> {
+        self.content.types.get(type_id).map(|t| t.class_id)
+    }
+
+    fn write_metadata(&self) -> BinResult<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    fn write_metadata_with_layout(
+        &self,
+        objects: &[super::Object],
+        data_offset: u64,
+        file_size: u64,
+    ) -> BinResult<Vec<u8>> {
+        let mut file = self.clone();
+        file.content.objects = objects
+            .iter()
+            .map(|obj| {
+                Ok(Object {
+                    path_id: obj.path_id,
+                    byte_start: layout_value_to_u32(obj.byte_start)?,
+                    byte_size: obj.byte_size,
+                    type_id: obj.type_id as i32,
+                })
+            })
+            .collect::<BinResult<_>>()?;
+        file.content.object_count = objects.len() as i32;
+        file.header.data_offset = layout_value_to_u32(data_offset)?;
+        file.header.file_size = layout_value_to_u32(file_size)?;
+        file.header.metadata_size = (file.write_metadata()?.len() - 20) as u32;
+        file.write_metadata()
+    }
+
+    fn add_type(
+        &mut self,
+        class_id: i32,
+        type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+    ) -> BinResult<usize> {
+        self.content.types.push(SerializedType {
+            class_id,
+            is_stripped_type: false.into(),
+            script_type_index: -1,
+            script_id: (class_id == 114).then_some([0u8; 16]),
+            old_type_hash: [0u8; 16],
+            type_tree: self
+                .content
+                .enable_type_tree
+                .then(|| TypeTree::from_type_fields(type_fields)),
+        });
+        self.content.type_count = self.content.types.len() as u32;
+        Ok(self.content.types.len() - 1)
+    }
 }
 
 #[binrw]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct SerializedFileContent {
     unity_version: NullString,
     target_platform: BuildTarget,
@@ -115,7 +198,7 @@ struct SerializedFileContent {
 
 #[binrw]
 #[br(import { enable_type_tree: bool})]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SerializedType {
     class_id: i32,
     is_stripped_type: U8Bool,
@@ -127,6 +210,34 @@ pub struct SerializedType {
     type_tree: Option<TypeTree>,
 }
 
+impl SerializedTypeEntry for SerializedType {
+    fn get_class_id(&self) -> i32 {
+        self.class_id
+    }
+
+    fn get_type_tree_node_count(&self) -> usize {
+        self.type_tree
+            .as_ref()
+            .map_or(0, |type_tree| type_tree.type_tree_node_blobs.len())
+    }
+
+    fn get_script_type_index(&self) -> Option<usize> {
+        usize::try_from(self.script_type_index).ok()
+    }
+
+    fn is_stripped_type(&self) -> Option<bool> {
+        Some(*self.is_stripped_type)
+    }
+
+    fn get_script_id(&self) -> Option<[u8; 16]> {
+        self.script_id
+    }
+
+    fn get_old_type_hash(&self) -> Option<[u8; 16]> {
+        Some(self.old_type_hash)
+    }
+}
+
 #[binrw]
 #[derive(Clone, PartialEq)]
 pub struct TypeTree {
@@ -138,6 +249,34 @@ pub struct TypeTree {
     pub string_buffer: Vec<u8>,
 }
 
+impl TypeTree {
+    pub fn from_type_fields(type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>]) -> Self {
+        let (string_offsets, string_buffer) = build_type_tree_string_buffer(type_fields);
+        let type_tree_node_blobs: Vec<_> = type_fields
+            .iter()
+            .zip(string_offsets)
+            .map(
+                |(field, (type_str_offset, name_str_offset))| TypeTreeNodeBlob {
+                    version: field.get_version(),
+                    level: field.get_level(),
+                    type_flags: field.get_type_flags(),
+                    type_str_offset,
+                    name_str_offset,
+                    byte_size: field.get_byte_size(),
+                    index: field.get_index(),
+                    meta_flag: field.get_meta_flag(),
+                },
+            )
+            .collect();
+        TypeTree {
+            number_of_nodes: type_tree_node_blobs.len() as i32,
+            string_buffer_size: string_buffer.len() as i32,
+            type_tree_node_blobs,
+            string_buffer,
+        }
+    }
+}
+
 impl fmt::Debug for TypeTree {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut string_reader = Cursor::new(&self.string_buffer);
@@ -185,6 +324,29 @@ impl TypeTreeNodeBlob {
     }
 }
 
+/// Build the string buffer of type tree, return the offsets of type and name of every field.
+pub fn build_type_tree_string_buffer(
+    type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+) -> (Vec<(u32, u32)>, Vec<u8>) {
+    let mut string_buffer = Vec::new();
+    let mut string_offsets: HashMap<String, u32> = HashMap::new();
+    let mut field_string_offsets = Vec::with_capacity(type_fields.len());
+    for field in type_fields {
+        let mut get_string_offset = |string: &String| {
+            *string_offsets.entry(string.clone()).or_insert_with(|| {
+                let offset = string_buffer.len() as u32;
+                string_buffer.extend_from_slice(string.as_bytes());
+                string_buffer.push(0);
+                offset
+            })
+        };
+        let type_str_offset = get_string_offset(field.get_type());
+        let name_str_offset = get_string_offset(field.get_name());
+        field_string_offsets.push((type_str_offset, name_str_offset));
+    }
+    (field_string_offsets, string_buffer)
+}
+
 pub fn read_type_tree_string<R: Read + Seek>(value: u32, reader: &mut R) -> String {
     let is_offset = (value & 0x80000000) == 0;
     if is_offset {
@@ -201,9 +363,9 @@ pub fn read_type_tree_string<R: Read + Seek>(value: u32, reader: &mut R) -> Stri
 }
 
 #[binrw]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Object {
-    #[br(align_before(4))]
+    #[brw(align_before(4))]
     pub path_id: i64,
     pub byte_start: u32,
     pub byte_size: u32,
@@ -211,11 +373,14 @@ pub struct Object {
 }
 
 #[binrw]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ScriptType {
-    local_serialized_file_index: i32,
-    #[br(align_before(4))]
-    local_identifier_in_file: i64,
+    /// Index into the externals, 0 for this file.
+    pub local_serialized_file_index: i32,
+    #[brw(align_before(4))]
+    /// Path id of the MonoScript object.
+    pub local_identifier_in_file: i64,
 }
 
 #[binrw]
@@ -250,6 +415,10 @@ impl TypeField for TypeTreeNode {
         self.node.type_flags & 1 > 0
     }
 
+    fn get_type_flags(&self) -> u8 {
+        self.node.type_flags
+    }
+
     fn get_byte_size(&self) -> i32 {
         self.node.byte_size
     }
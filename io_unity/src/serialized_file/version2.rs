@@ -2,7 +2,7 @@ use super::version11::TypeTreeNode;
 use super::version4::FileIdentifier;
 use super::version6::Object;
 use super::{BuildTarget, SerializedFileCommonHeader};
-use super::{Serialized, SerializedFileFormatVersion};
+use super::{Serialized, SerializedFileFormatVersion, SerializedTypeEntry, SerializedTypeInfo};
 use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::until::Endian;
 use binrw::{binrw, NullString};
@@ -29,6 +29,14 @@ impl Serialized for SerializedFile {
         self.header.data_offset as u64
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header.file_size as u64
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -43,6 +51,7 @@ impl Serialized for SerializedFile {
                 byte_size: obj.byte_size,
                 class: obj.class_id as i32,
                 type_id: obj.type_id as usize,
+                script_type_index: -1,
             })
             .collect()
     }
@@ -110,6 +119,10 @@ impl Serialized for SerializedFile {
             .collect();
         return Cow::Owned(externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
 }
 
 #[binrw]
@@ -133,6 +146,16 @@ pub struct SerializedType {
     pub type_tree: TypeTree,
 }
 
+impl SerializedTypeEntry for SerializedType {
+    fn get_class_id(&self) -> i32 {
+        self.class_id
+    }
+
+    fn get_type_tree_node_count(&self) -> usize {
+        self.type_tree.get_node_count()
+    }
+}
+
 #[binrw]
 #[br(import { level: i32 = 0})]
 #[derive(Debug, Clone, PartialEq)]
@@ -151,3 +174,13 @@ pub struct TypeTree {
     #[br(args { count: children_count as usize, inner: TypeTreeBinReadArgs::builder().level(level + 1).finalize() })]
     pub children: Vec<TypeTree>,
 }
+
+impl TypeTree {
+    pub fn get_node_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(TypeTree::get_node_count)
+            .sum::<usize>()
+    }
+}
@@ -1,22 +1,26 @@
 use super::version17::{FileIdentifier, Object, ScriptType};
-use super::version19::{SerializedType, SerializedTypeBinReadArgs, TypeTreeNode};
-use super::{BuildTarget, SerializedFileCommonHeader};
-use super::{Serialized, SerializedFileFormatVersion};
-use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
+use super::version19::{SerializedType, SerializedTypeBinReadArgs, TypeTree, TypeTreeNode};
+use super::{layout_value_to_u32, BuildTarget, SerializedFileCommonHeader};
+use super::{Serialized, SerializedFileFormatVersion, SerializedTypeInfo};
+use crate::type_tree::{
+    reader::{TypeTreeObjectBinReadClassArgs, TypeTreeRefType},
+    TypeField,
+};
 use crate::until::{binrw_parser::*, Endian};
 use binrw::io::Cursor;
-use binrw::{binrw, NullString};
+use binrw::{binrw, BinResult, BinWrite, NullString};
 use std::borrow::Cow;
 use std::sync::Arc;
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SerializedFile {
     header: SerializedFileCommonHeader,
     endianess: Endian,
     reserved: [u8; 3],
     #[br(is_little = endianess == Endian::Little)]
+    #[bw(is_little = *endianess == Endian::Little)]
     content: SerializedFileContent,
 }
 
@@ -29,6 +33,14 @@ impl Serialized for SerializedFile {
         self.header.data_offset as u64
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header.file_size as u64
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -48,6 +60,12 @@ impl Serialized for SerializedFile {
                     .map(|t| t.class_id)
                     .unwrap_or(0),
                 type_id: obj.type_id as usize,
+                script_type_index: self
+                    .content
+                    .types
+                    .get(obj.type_id as usize)
+                    .map(|t| t.script_type_index)
+                    .unwrap_or(-1),
             })
             .collect()
     }
@@ -81,18 +99,101 @@ impl Serialized for SerializedFile {
             }) as Box<dyn TypeField + Send + Sync>))
         }
 
-        Some(TypeTreeObjectBinReadClassArgs::new(
-            stypetree.class_id,
-            type_fields,
-        ))
+        Some(
+            TypeTreeObjectBinReadClassArgs::new(stypetree.class_id, type_fields).with_ref_types(
+                self.content
+                    .ref_types
+                    .iter()
+                    .filter_map(|ref_type| {
+                        // ref types of version 20 do not store the class name, namespace and assembly name,
+                        // so they are matched by the type of root node only.
+                        let type_fields = ref_type.type_tree.as_ref()?.get_type_fields();
+                        let class_name = type_fields.first()?.get_type().clone();
+                        Some(TypeTreeRefType::new(
+                            class_name,
+                            String::new(),
+                            String::new(),
+                            type_fields,
+                        ))
+                    })
+                    .collect(),
+            ),
+        )
     }
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>> {
         return Cow::Borrowed(&self.content.externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
+
+    fn get_user_information(&self) -> Option<String> {
+        Some(self.content.user_information.to_string())
+    }
+
+    fn get_script_types(&self) -> Cow<Vec<ScriptType>> {
+        Cow::Borrowed(&self.content.script_types)
+    }
+
+    fn get_type_class_id(&self, type_id: usize) -> Option<i32> {
+        self.content.types.get(type_id).map(|t| t.class_id)
+    }
+
+    fn write_metadata(&self) -> BinResult<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    fn write_metadata_with_layout(
+        &self,
+        objects: &[super::Object],
+        data_offset: u64,
+        file_size: u64,
+    ) -> BinResult<Vec<u8>> {
+        let mut file = self.clone();
+        file.content.objects = objects
+            .iter()
+            .map(|obj| {
+                Ok(Object {
+                    path_id: obj.path_id,
+                    byte_start: layout_value_to_u32(obj.byte_start)?,
+                    byte_size: obj.byte_size,
+                    type_id: obj.type_id as i32,
+                })
+            })
+            .collect::<BinResult<_>>()?;
+        file.content.object_count = objects.len() as i32;
+        file.header.data_offset = layout_value_to_u32(data_offset)?;
+        file.header.file_size = layout_value_to_u32(file_size)?;
+        file.header.metadata_size = (file.write_metadata()?.len() - 20) as u32;
+        file.write_metadata()
+    }
+
+    fn add_type(
+        &mut self,
+        class_id: i32,
+        type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+    ) -> BinResult<usize> {
+        self.content.types.push(SerializedType {
+            class_id,
+            is_stripped_type: false.into(),
+            script_type_index: -1,
+            script_id: (class_id == 114).then_some([0u8; 16]),
+            old_type_hash: [0u8; 16],
+            type_tree: self
+                .content
+                .enable_type_tree
+                .then(|| TypeTree::from_type_fields(type_fields)),
+        });
+        self.content.type_count = self.content.types.len() as u32;
+        Ok(self.content.types.len() - 1)
+    }
 }
 
 #[binrw]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct SerializedFileContent {
     unity_version: NullString,
     target_platform: BuildTarget,
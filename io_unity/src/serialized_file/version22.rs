@@ -4,17 +4,17 @@ use super::version21::{
     SerializedRefType, SerializedRefTypeBinReadArgs, SerializedType, SerializedTypeBinReadArgs,
 };
 use super::{BuildTarget, SerializedFileCommonHeader};
-use super::{Serialized, SerializedFileFormatVersion};
+use super::{Serialized, SerializedFileFormatVersion, SerializedTypeInfo};
 use crate::type_tree::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::until::{binrw_parser::*, Endian};
 use binrw::io::Cursor;
-use binrw::{binrw, NullString};
+use binrw::{binrw, BinResult, BinWrite, NullString};
 use std::borrow::Cow;
 use std::sync::Arc;
 
 #[binrw]
-#[br(big)]
-#[derive(Debug, Eq, PartialEq)]
+#[brw(big)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 struct SerializedFileHeader {
     metadata_size: u32,
     file_size: u64,
@@ -24,13 +24,14 @@ struct SerializedFileHeader {
 
 #[binrw]
 #[brw(big)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SerializedFile {
     header: SerializedFileCommonHeader,
     endianess: Endian,
     reserved: [u8; 3],
     header2: SerializedFileHeader,
     #[br(is_little = endianess == Endian::Little)]
+    #[bw(is_little = *endianess == Endian::Little)]
     content: SerializedFileContent,
 }
 
@@ -43,6 +44,14 @@ impl Serialized for SerializedFile {
         self.header2.data_offset
     }
 
+    fn get_file_size(&self) -> u64 {
+        self.header2.file_size
+    }
+
+    fn get_metadata_size(&self) -> u64 {
+        self.header2.metadata_size as u64
+    }
+
     fn get_endianess(&self) -> &Endian {
         &self.endianess
     }
@@ -62,6 +71,12 @@ impl Serialized for SerializedFile {
                     .map(|t| t.class_id)
                     .unwrap_or(0),
                 type_id: obj.type_id as usize,
+                script_type_index: self
+                    .content
+                    .types
+                    .get(obj.type_id as usize)
+                    .map(|t| t.script_type_index)
+                    .unwrap_or(-1),
             })
             .collect()
     }
@@ -95,19 +110,85 @@ impl Serialized for SerializedFile {
             }) as Box<dyn TypeField + Send + Sync>))
         }
 
-        Some(TypeTreeObjectBinReadClassArgs::new(
-            stypetree.class_id,
-            type_fields,
-        ))
+        Some(
+            TypeTreeObjectBinReadClassArgs::new(stypetree.class_id, type_fields).with_ref_types(
+                self.content
+                    .ref_types
+                    .iter()
+                    .filter_map(SerializedRefType::get_ref_type)
+                    .collect(),
+            ),
+        )
     }
 
     fn get_externals(&self) -> Cow<Vec<FileIdentifier>> {
         return Cow::Borrowed(&self.content.externals);
     }
+
+    fn get_type_infos(&self) -> Vec<SerializedTypeInfo> {
+        super::get_type_infos(&self.content.types)
+    }
+
+    fn get_user_information(&self) -> Option<String> {
+        Some(self.content.user_information.to_string())
+    }
+
+    fn get_script_types(&self) -> Cow<Vec<ScriptType>> {
+        Cow::Borrowed(&self.content.script_types)
+    }
+
+    fn get_type_class_id(&self, type_id: usize) -> Option<i32> {
+        self.content.types.get(type_id).map(|t| t.class_id)
+    }
+
+    fn write_metadata(&self) -> BinResult<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    fn write_metadata_with_layout(
+        &self,
+        objects: &[super::Object],
+        data_offset: u64,
+        file_size: u64,
+    ) -> BinResult<Vec<u8>> {
+        let mut file = self.clone();
+        file.content.objects = objects
+            .iter()
+            .map(|obj| {
+                Ok(Object {
+                    path_id: obj.path_id,
+                    byte_start: obj.byte_start,
+                    byte_size: obj.byte_size,
+                    type_id: obj.type_id as i32,
+                })
+            })
+            .collect::<BinResult<_>>()?;
+        file.content.object_count = objects.len() as i32;
+        file.header2.data_offset = data_offset;
+        file.header2.file_size = file_size;
+        file.header2.metadata_size = (file.write_metadata()?.len() - 48) as u32;
+        file.write_metadata()
+    }
+
+    fn add_type(
+        &mut self,
+        class_id: i32,
+        type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+    ) -> BinResult<usize> {
+        self.content.types.push(SerializedType::new(
+            class_id,
+            *self.content.enable_type_tree,
+            type_fields,
+        ));
+        self.content.type_count = self.content.types.len() as u32;
+        Ok(self.content.types.len() - 1)
+    }
 }
 
 #[binrw]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct SerializedFileContent {
     pub unity_version: NullString,
     pub target_platform: BuildTarget,
@@ -131,9 +212,9 @@ pub struct SerializedFileContent {
 }
 
 #[binrw]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Object {
-    #[br(align_before(4))]
+    #[brw(align_before(4))]
     pub path_id: i64,
     pub byte_start: u64,
     pub byte_size: u32,
@@ -0,0 +1,161 @@
+use crate::resource::FileOpenMode;
+use crate::unityfs::UnityResource;
+use std::io::{prelude::*, ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Parts of a file split by unity (`x.assets.split0`, `x.assets.split1`, ...)
+/// joined into one seekable resource.
+pub struct SplitResource {
+    parts: Vec<(u64, Box<dyn UnityResource + Send + Sync>)>,
+    size: u64,
+    current_position: u64,
+}
+
+impl SplitResource {
+    pub fn new(parts: Vec<Box<dyn UnityResource + Send + Sync>>) -> std::io::Result<Self> {
+        let mut size = 0;
+        let mut parts_with_offset = Vec::with_capacity(parts.len());
+        for mut part in parts {
+            let part_size = part.seek(SeekFrom::End(0))?;
+            // empty parts would end read early.
+            if part_size > 0 {
+                parts_with_offset.push((size, part));
+                size += part_size;
+            }
+        }
+        Ok(Self {
+            parts: parts_with_offset,
+            size,
+            current_position: 0,
+        })
+    }
+
+    /// Open `path.split0`, `path.split1`, ... until a part is missing.
+    /// Return `None` if `path.split0` does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Option<Self>> {
+        Self::open_with_mode(path, FileOpenMode::default())
+    }
+
+    /// Same as [`SplitResource::open`], but open parts with `file_open_mode`.
+    pub fn open_with_mode<P: AsRef<Path>>(
+        path: P,
+        file_open_mode: FileOpenMode,
+    ) -> std::io::Result<Option<Self>> {
+        let mut parts: Vec<Box<dyn UnityResource + Send + Sync>> = Vec::new();
+        loop {
+            let mut part_path = path.as_ref().as_os_str().to_owned();
+            part_path.push(format!(".split{}", parts.len()));
+            match file_open_mode.open(part_path) {
+                Ok(file) => parts.push(file),
+                Err(err) if err.kind() == ErrorKind::NotFound => break,
+                Err(err) => return Err(err),
+            }
+        }
+        if parts.is_empty() {
+            return Ok(None);
+        }
+        Self::new(parts).map(Some)
+    }
+
+    /// Return the path without `.splitN` suffix and the part index `N`,
+    /// or `None` if the path is not a split part.
+    pub fn get_split_base_path<P: AsRef<Path>>(path: P) -> Option<(PathBuf, usize)> {
+        let file_name = path.as_ref().file_name()?.to_str()?;
+        let (base_file_name, part_index) = file_name.rsplit_once(".split")?;
+        let part_index = part_index.parse().ok()?;
+        Some((path.as_ref().with_file_name(base_file_name), part_index))
+    }
+
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for SplitResource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.current_position >= self.size {
+            return Ok(0);
+        }
+        let part_index = match self
+            .parts
+            .binary_search_by_key(&self.current_position, |(part_offset, _)| *part_offset)
+        {
+            Ok(index) => index,
+            Err(rindex) => rindex - 1,
+        };
+        let part_end = self
+            .parts
+            .get(part_index + 1)
+            .map_or(self.size, |(part_offset, _)| *part_offset);
+        let (part_offset, part) = &mut self.parts[part_index];
+        let read_size = (buf.len() as u64).min(part_end - self.current_position) as usize;
+        part.seek(SeekFrom::Start(self.current_position - *part_offset))?;
+        let read_size = part.read(&mut buf[..read_size])?;
+        self.current_position += read_size as u64;
+        Ok(read_size)
+    }
+}
+
+impl Seek for SplitResource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::End(offset) => self.size as i64 + offset,
+            SeekFrom::Current(offset) => self.current_position as i64 + offset,
+        };
+        if new_pos < 0 {
+            return Err(std::io::Error::from(ErrorKind::InvalidInput));
+        }
+        self.current_position = new_pos as u64;
+        Ok(self.current_position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::open_resource_file;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_split_resource() {
+        let data: Vec<u8> = (0..2500u32).map(|i| (i % 251) as u8).collect();
+        let mut split_resource = SplitResource::new(
+            data.chunks(1000)
+                .map(|part| {
+                    Box::new(Cursor::new(part.to_vec())) as Box<dyn UnityResource + Send + Sync>
+                })
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(split_resource.get_size(), 2500);
+        let mut joined = Vec::new();
+        split_resource.read_to_end(&mut joined).unwrap();
+        assert_eq!(joined, data);
+
+        let mut buff = [0u8; 20];
+        split_resource.seek(SeekFrom::End(-1510)).unwrap();
+        split_resource.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, data[990..1010]);
+
+        let dir = std::env::temp_dir().join(format!("io_unity_split_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (i, part) in data.chunks(1000).enumerate() {
+            std::fs::write(dir.join(format!("sharedassets0.assets.split{i}")), part).unwrap();
+        }
+        let base_path = dir.join("sharedassets0.assets");
+        assert_eq!(
+            SplitResource::get_split_base_path(dir.join("sharedassets0.assets.split2")),
+            Some((base_path.clone(), 2))
+        );
+        assert_eq!(SplitResource::get_split_base_path(&base_path), None);
+        let mut joined = Vec::new();
+        open_resource_file(&base_path)
+            .unwrap()
+            .read_to_end(&mut joined)
+            .unwrap();
+        assert_eq!(joined, data);
+        assert!(open_resource_file(dir.join("missing.assets")).is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 pub mod convert;
+pub mod provider;
 pub mod reader;
 #[cfg(feature = "type-tree-json")]
 pub mod type_tree_json;
@@ -20,6 +21,10 @@ pub trait TypeField: Debug {
     fn get_version(&self) -> u16;
     fn get_level(&self) -> u8;
     fn is_array(&self) -> bool;
+    /// 0x01 : IsArray, 0x02 : IsRef, 0x04 : IsRegistry, 0x08 : IsArrayOfRefs
+    fn get_type_flags(&self) -> u8 {
+        self.is_array() as u8
+    }
     fn get_byte_size(&self) -> i32;
     fn get_index(&self) -> i32;
     fn get_meta_flag(&self) -> i32;
@@ -302,6 +307,23 @@ impl TypeTreeObject {
         feild.try_as_slice(&self.data_buff, &field_cast_args)
     }
 
+    /// Same as [`TypeTreeObject::try_as_slice`], for edit fixed size fields in place.
+    pub fn try_as_mut_slice(&mut self, path: &str) -> ReadResult<&mut [u8]> {
+        let slice = self.try_as_slice(path)?;
+        let start = slice.as_ptr() as usize - self.data_buff.as_ptr() as usize;
+        let end = start + slice.len();
+        Ok(&mut self.data_buff[start..end])
+    }
+
+    /// Serialized data of object, include the external data after fields.
+    pub fn get_object_data(&self) -> Vec<u8> {
+        let mut data = self.data_buff.clone();
+        if let Some(external_data) = &self.external_data {
+            data.extend_from_slice(external_data);
+        }
+        data
+    }
+
     pub(super) fn get_field_by_path(&self, path: &str) -> ReadResult<(&Field, Option<i64>)> {
         let path: Vec<String> = path
             .split('/')
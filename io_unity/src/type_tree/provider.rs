@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use super::reader::TypeTreeObjectBinReadClassArgs;
+
+/// Source of type trees for serialized files without type tree, keyed by unity version and class id.
+///
+/// Type trees of files with type tree can be added to the provider by
+/// [`crate::serialized_file::SerializedFile::add_type_trees_to_provider`],
+/// so they can be used by other files of the same version.
+pub trait TypeTreeProvider: Send + Sync {
+    fn get_type_object_args(
+        &self,
+        version: &str,
+        class_id: i32,
+    ) -> Option<TypeTreeObjectBinReadClassArgs>;
+
+    /// Whether the type tree is already added or loaded, which is not built again from files.
+    fn has_type_object_args(&self, _version: &str, _class_id: i32) -> bool {
+        false
+    }
+
+    /// Add type trees read from files, type tree of class already added should not be replaced.
+    /// Providers which do not keep them can ignore.
+    fn add_type_object_args(
+        &self,
+        _version: &str,
+        _class_args_list: Vec<TypeTreeObjectBinReadClassArgs>,
+    ) {
+    }
+}
+
+/// Provider keep type trees in memory, start with empty or filled by [`TypeTreeProvider::add_type_object_args`].
+#[derive(Debug, Default)]
+pub struct MemoryTypeTreeProvider {
+    versions: RwLock<HashMap<String, BTreeMap<i32, TypeTreeObjectBinReadClassArgs>>>,
+}
+
+impl MemoryTypeTreeProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_versions(&self) -> Vec<String> {
+        self.versions
+            .read()
+            .map(|versions| versions.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl TypeTreeProvider for MemoryTypeTreeProvider {
+    fn get_type_object_args(
+        &self,
+        version: &str,
+        class_id: i32,
+    ) -> Option<TypeTreeObjectBinReadClassArgs> {
+        self.versions
+            .read()
+            .ok()?
+            .get(version)?
+            .get(&class_id)
+            .cloned()
+    }
+
+    fn has_type_object_args(&self, version: &str, class_id: i32) -> bool {
+        self.versions.read().is_ok_and(|versions| {
+            versions
+                .get(version)
+                .is_some_and(|class_map| class_map.contains_key(&class_id))
+        })
+    }
+
+    fn add_type_object_args(
+        &self,
+        version: &str,
+        class_args_list: Vec<TypeTreeObjectBinReadClassArgs>,
+    ) {
+        if let Ok(mut versions) = self.versions.write() {
+            let class_map = versions.entry(version.to_owned()).or_default();
+            for class_args in class_args_list {
+                class_map
+                    .entry(class_args.get_class_id())
+                    .or_insert(class_args);
+            }
+        }
+    }
+}
+
+/// Provider used by [`crate::unity_asset_view::UnityAssetViewer`] when no provider is set, which is the process wide InfoJson provider
+/// set by [`super::type_tree_json::set_info_json_tar_reader`] with feature `type-tree-json`.
+pub fn get_default_type_tree_provider() -> Option<Arc<dyn TypeTreeProvider>> {
+    #[cfg(feature = "type-tree-json")]
+    {
+        Some(super::type_tree_json::get_default_info_json_provider())
+    }
+    #[cfg(not(feature = "type-tree-json"))]
+    {
+        None
+    }
+}
@@ -39,6 +39,7 @@ impl TypeTreeObjectBinReadArgs {
 pub struct TypeTreeObjectBinReadClassArgs {
     class_id: i32,
     type_fields: Vec<Arc<Box<dyn TypeField + Send + Sync>>>,
+    ref_types: Arc<Vec<TypeTreeRefType>>,
 }
 
 impl TypeTreeObjectBinReadClassArgs {
@@ -46,8 +47,155 @@ impl TypeTreeObjectBinReadClassArgs {
         Self {
             class_id,
             type_fields,
+            ref_types: Arc::new(Vec::new()),
         }
     }
+
+    /// Type trees used to decode `[SerializeReference]` objects in the managed references registry.
+    pub fn with_ref_types(mut self, ref_types: Vec<TypeTreeRefType>) -> Self {
+        self.ref_types = Arc::new(ref_types);
+        self
+    }
+
+    pub fn get_ref_types(&self) -> &Vec<TypeTreeRefType> {
+        &self.ref_types
+    }
+
+    pub fn get_class_id(&self) -> i32 {
+        self.class_id
+    }
+
+    pub fn get_type_fields(&self) -> &Vec<Arc<Box<dyn TypeField + Send + Sync>>> {
+        &self.type_fields
+    }
+}
+
+/// Type tree of a managed reference type, stored in the ref types of serialized file.
+/// Namespace and assembly name are empty if the file does not store them,
+/// then the type is matched by class name only.
+#[derive(Debug, Clone)]
+pub struct TypeTreeRefType {
+    class_name: String,
+    namespace: String,
+    assembly_name: String,
+    type_fields: Vec<Arc<Box<dyn TypeField + Send + Sync>>>,
+}
+
+impl TypeTreeRefType {
+    pub fn new(
+        class_name: String,
+        namespace: String,
+        assembly_name: String,
+        type_fields: Vec<Arc<Box<dyn TypeField + Send + Sync>>>,
+    ) -> Self {
+        Self {
+            class_name,
+            namespace,
+            assembly_name,
+            type_fields,
+        }
+    }
+
+    pub fn get_class_name(&self) -> &String {
+        &self.class_name
+    }
+
+    pub fn get_namespace(&self) -> &String {
+        &self.namespace
+    }
+
+    pub fn get_assembly_name(&self) -> &String {
+        &self.assembly_name
+    }
+
+    pub fn get_type_fields(&self) -> &Vec<Arc<Box<dyn TypeField + Send + Sync>>> {
+        &self.type_fields
+    }
+}
+
+const TYPE_FLAG_IS_REF: u8 = 0x02;
+const TYPE_FLAG_IS_REGISTRY: u8 = 0x04;
+
+/// `ReferencedObjectData` node, the payload type is chosen by the type of referenced object.
+fn is_managed_reference(field: &Arc<Box<dyn TypeField + Send + Sync>>) -> bool {
+    field.get_type_flags() & TYPE_FLAG_IS_REF > 0 || field.get_type() == "ReferencedObjectData"
+}
+
+fn is_managed_references_registry(field: &Arc<Box<dyn TypeField + Send + Sync>>) -> bool {
+    field.get_type_flags() & TYPE_FLAG_IS_REGISTRY > 0
+        || field.get_type() == "ManagedReferencesRegistry"
+}
+
+/// Value of `ReferencedManagedType` field.
+#[derive(Debug, PartialEq)]
+struct ManagedTypeName {
+    class_name: String,
+    namespace: String,
+    assembly_name: String,
+}
+
+impl ManagedTypeName {
+    fn read<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<Self> {
+        Ok(Self {
+            class_name: read_aligned_string(reader, endian)?,
+            namespace: read_aligned_string(reader, endian)?,
+            assembly_name: read_aligned_string(reader, endian)?,
+        })
+    }
+
+    /// Registry version 1 has no object count, the objects end with this type.
+    fn is_terminus(&self) -> bool {
+        self.class_name == "Terminus"
+            && self.namespace == "UnityEngine.DMAT"
+            && self.assembly_name == "FAKE_ASM"
+    }
+
+    /// The data of reference can not be skipped without its type tree.
+    fn not_found_error(&self) -> binrw::Error {
+        std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "type tree of managed reference type {:?} in namespace {:?} of assembly {:?} is not found",
+                self.class_name, self.namespace, self.assembly_name
+            ),
+        )
+        .into()
+    }
+
+    fn find_ref_type<'a>(&self, ref_types: &'a [TypeTreeRefType]) -> Option<&'a TypeTreeRefType> {
+        ref_types.iter().find(|ref_type| {
+            ref_type.class_name == self.class_name
+                && ((ref_type.namespace == self.namespace
+                    && ref_type.assembly_name == self.assembly_name)
+                    || (ref_type.namespace.is_empty() && ref_type.assembly_name.is_empty()))
+        })
+    }
+}
+
+fn read_aligned_string<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<String> {
+    let size = i32::read_options(reader, endian, ())?;
+    if size < 0 {
+        return Err(std::io::Error::from(ErrorKind::InvalidData).into());
+    }
+    let data = <Vec<u8>>::read_options(
+        reader,
+        endian,
+        VecArgs {
+            count: size as usize,
+            inner: (),
+        },
+    )?;
+    align_reader(reader, &mut 0)?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+fn align_reader<R: Read + Seek>(reader: &mut R, read_offset: &mut u64) -> BinResult<()> {
+    let pos = reader.stream_position()?;
+    if pos % 4 != 0 {
+        reader.seek(SeekFrom::Current((4 - (pos % 4)) as i64))?;
+        *read_offset += 4 - (pos % 4);
+    }
+    Ok(())
 }
 
 impl BinRead for TypeTreeObject {
@@ -65,6 +213,7 @@ impl BinRead for TypeTreeObject {
             field_index: &mut usize,
             read_offset: &mut u64,
             is_fix_size_array_item: bool,
+            ref_types: &[TypeTreeRefType],
         ) -> BinResult<Field> {
             let field = type_fields
                 .get(*field_index)
@@ -74,8 +223,15 @@ impl BinRead for TypeTreeObject {
                 assert!(!is_fix_size_array_item);
                 *field_index += 1;
                 let size_start_pos = reader.stream_position()?;
-                let size_field =
-                    read(reader, endian, type_fields, field_index, read_offset, false)?;
+                let size_field = read(
+                    reader,
+                    endian,
+                    type_fields,
+                    field_index,
+                    read_offset,
+                    false,
+                    ref_types,
+                )?;
                 reader.seek(SeekFrom::Start(size_start_pos))?;
                 let size: i32 = size_field
                     .try_read_to(
@@ -128,6 +284,7 @@ impl BinRead for TypeTreeObject {
                         &mut 0,
                         &mut item_field_offset,
                         true,
+                        ref_types,
                     )?;
 
                     *read_offset += (byte_size * size as usize) as u64;
@@ -161,6 +318,7 @@ impl BinRead for TypeTreeObject {
                             field_index,
                             read_offset,
                             false,
+                            ref_types,
                         )?);
                     }
 
@@ -181,9 +339,89 @@ impl BinRead for TypeTreeObject {
             } else if let Some(next_field) = type_fields.get(*field_index + 1) {
                 if next_field.get_level() == field_level + 1 {
                     let mut fields = HashMap::new();
+                    let mut managed_type: Option<ManagedTypeName> = None;
                     while let Some(next_field) = type_fields.get(*field_index + 1) {
                         if next_field.get_level() == field_level + 1 {
                             *field_index += 1;
+                            if is_managed_reference(next_field) {
+                                while let Some(child_field) = type_fields.get(*field_index + 1) {
+                                    if child_field.get_level() <= next_field.get_level() {
+                                        break;
+                                    }
+                                    *field_index += 1;
+                                }
+                                let data = match managed_type.take() {
+                                    // null reference and the end of registry have no data.
+                                    Some(managed_type)
+                                        if managed_type.class_name.is_empty()
+                                            || managed_type.is_terminus() =>
+                                    {
+                                        FieldValue::Fields(HashMap::new())
+                                    }
+                                    Some(managed_type) => {
+                                        let ref_type = managed_type
+                                            .find_ref_type(ref_types)
+                                            .ok_or_else(|| managed_type.not_found_error())?;
+                                        read(
+                                            reader,
+                                            endian,
+                                            &ref_type.type_fields,
+                                            &mut 0,
+                                            read_offset,
+                                            false,
+                                            ref_types,
+                                        )?
+                                        .data
+                                    }
+                                    None => {
+                                        return Err(std::io::Error::new(
+                                            ErrorKind::InvalidData,
+                                            format!(
+                                                "managed reference {:?} has no type",
+                                                next_field.get_name()
+                                            ),
+                                        )
+                                        .into())
+                                    }
+                                };
+                                if next_field.is_align() {
+                                    align_reader(reader, read_offset)?;
+                                }
+                                fields.insert(
+                                    next_field.get_name().clone(),
+                                    Field {
+                                        field_type: next_field.clone(),
+                                        data,
+                                    },
+                                );
+                                continue;
+                            }
+                            if is_managed_references_registry(field)
+                                && next_field.get_type() == "ReferencedObject"
+                            {
+                                let item_field_index = *field_index;
+                                for index in 0.. {
+                                    *field_index = item_field_index;
+                                    let item_start_pos = reader.stream_position()?;
+                                    let item_type = ManagedTypeName::read(reader, endian)?;
+                                    reader.seek(SeekFrom::Start(item_start_pos))?;
+                                    let field_data = read(
+                                        reader,
+                                        endian,
+                                        type_fields,
+                                        field_index,
+                                        read_offset,
+                                        is_fix_size_array_item,
+                                        ref_types,
+                                    )?;
+                                    if item_type.is_terminus() {
+                                        break;
+                                    }
+                                    fields.insert(format!("{index:08}"), field_data);
+                                }
+                                continue;
+                            }
+                            let field_start_pos = reader.stream_position()?;
                             let field_data = read(
                                 reader,
                                 endian,
@@ -191,7 +429,14 @@ impl BinRead for TypeTreeObject {
                                 field_index,
                                 read_offset,
                                 is_fix_size_array_item,
+                                ref_types,
                             )?;
+                            if next_field.get_type() == "ReferencedManagedType" {
+                                let field_end_pos = reader.stream_position()?;
+                                reader.seek(SeekFrom::Start(field_start_pos))?;
+                                managed_type = Some(ManagedTypeName::read(reader, endian)?);
+                                reader.seek(SeekFrom::Start(field_end_pos))?;
+                            }
                             fields.insert(field_data.get_name().clone(), field_data);
                         } else if next_field.get_level() <= field_level {
                             break;
@@ -232,11 +477,7 @@ impl BinRead for TypeTreeObject {
             };
 
             if field.is_align() {
-                let pos = reader.stream_position()?;
-                if pos % 4 != 0 {
-                    reader.seek(SeekFrom::Current((4 - (pos % 4)) as i64))?;
-                    *read_offset += 4 - (pos % 4);
-                }
+                align_reader(reader, read_offset)?;
             }
             // dbg!(reader.stream_position());
             // dbg!(&field_value.data);
@@ -254,6 +495,7 @@ impl BinRead for TypeTreeObject {
             &mut index,
             &mut data_buff_offset,
             false,
+            &args.class_args.ref_types,
         )?;
         reader.seek(SeekFrom::Start(start_pos))?;
 
@@ -309,3 +551,282 @@ fn calc_no_array_field_size(
     }
     Some(*read_size)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::type_tree::convert::TryCastFrom;
+    use binrw::io::Cursor;
+
+    #[derive(Debug)]
+    struct TestField {
+        level: u8,
+        type_flags: u8,
+        byte_size: i32,
+        meta_flag: i32,
+        type_name: String,
+        name: String,
+    }
+
+    impl TypeField for TestField {
+        fn get_version(&self) -> u16 {
+            1
+        }
+
+        fn get_level(&self) -> u8 {
+            self.level
+        }
+
+        fn is_array(&self) -> bool {
+            self.type_flags & 1 > 0
+        }
+
+        fn get_type_flags(&self) -> u8 {
+            self.type_flags
+        }
+
+        fn get_byte_size(&self) -> i32 {
+            self.byte_size
+        }
+
+        fn get_index(&self) -> i32 {
+            0
+        }
+
+        fn get_meta_flag(&self) -> i32 {
+            self.meta_flag
+        }
+
+        fn is_align(&self) -> bool {
+            self.meta_flag & 0x4000 > 0
+        }
+
+        fn get_ref_type_hash(&self) -> Option<u64> {
+            None
+        }
+
+        fn get_type(&self) -> &String {
+            &self.type_name
+        }
+
+        fn get_name(&self) -> &String {
+            &self.name
+        }
+    }
+
+    fn push_field(
+        fields: &mut Vec<Arc<Box<dyn TypeField + Send + Sync>>>,
+        level: u8,
+        type_name: &str,
+        name: &str,
+        byte_size: i32,
+        type_flags: u8,
+        meta_flag: i32,
+    ) {
+        fields.push(Arc::new(Box::new(TestField {
+            level,
+            type_flags,
+            byte_size,
+            meta_flag,
+            type_name: type_name.to_owned(),
+            name: name.to_owned(),
+        })));
+    }
+
+    fn push_referenced_object(
+        fields: &mut Vec<Arc<Box<dyn TypeField + Send + Sync>>>,
+        level: u8,
+        name: &str,
+        with_rid: bool,
+    ) {
+        push_field(fields, level, "ReferencedObject", name, -1, 0, 0);
+        if with_rid {
+            push_field(fields, level + 1, "SInt64", "rid", 8, 0, 0);
+        }
+        push_field(fields, level + 1, "ReferencedManagedType", "type", -1, 0, 0);
+        for name in ["class", "ns", "asm"] {
+            push_field(fields, level + 2, "string", name, -1, 0, 0x8000);
+            push_field(fields, level + 3, "Array", "Array", -1, 1, 0x4000);
+            push_field(fields, level + 4, "int", "size", 4, 0, 0);
+            push_field(fields, level + 4, "char", "data", 1, 0, 0);
+        }
+        push_field(fields, level + 1, "ReferencedObjectData", "data", 0, 2, 0);
+    }
+
+    fn push_string(data: &mut Vec<u8>, value: &str) {
+        data.extend((value.len() as i32).to_le_bytes());
+        data.extend(value.as_bytes());
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+
+    fn get_ref_types() -> Vec<TypeTreeRefType> {
+        let mut type_fields = Vec::new();
+        push_field(&mut type_fields, 0, "TestRef", "Base", -1, 0, 0);
+        push_field(&mut type_fields, 1, "int", "m_X", 4, 0, 0);
+        vec![TypeTreeRefType::new(
+            "TestRef".to_owned(),
+            "Test".to_owned(),
+            "Assembly-CSharp".to_owned(),
+            type_fields,
+        )]
+    }
+
+    fn read_object(
+        type_fields: Vec<Arc<Box<dyn TypeField + Send + Sync>>>,
+        data: Vec<u8>,
+    ) -> TypeTreeObject {
+        let class_args =
+            TypeTreeObjectBinReadClassArgs::new(114, type_fields).with_ref_types(get_ref_types());
+        let data_size = data.len();
+        let obj = TypeTreeObject::read_options(
+            &mut Cursor::new(data),
+            Endian::Little,
+            TypeTreeObjectBinReadArgs::new(0, 1, class_args),
+        )
+        .unwrap();
+        assert_eq!(obj.get_object_data().len(), data_size);
+        obj
+    }
+
+    #[test]
+    fn test_managed_references_registry() {
+        let mut type_fields = Vec::new();
+        push_field(&mut type_fields, 0, "MonoBehaviour", "Base", -1, 0, 0);
+        push_field(&mut type_fields, 1, "int", "m_Value", 4, 0, 0);
+        push_field(
+            &mut type_fields,
+            1,
+            "ManagedReferencesRegistry",
+            "references",
+            -1,
+            4,
+            0,
+        );
+        push_field(&mut type_fields, 2, "int", "version", 4, 0, 0);
+        push_field(&mut type_fields, 2, "vector", "RefIds", -1, 0, 0);
+        push_field(&mut type_fields, 3, "Array", "Array", -1, 1, 0);
+        push_field(&mut type_fields, 4, "int", "size", 4, 0, 0);
+        push_referenced_object(&mut type_fields, 4, "data", true);
+
+        let mut data = Vec::new();
+        data.extend(5i32.to_le_bytes());
+        data.extend(2i32.to_le_bytes());
+        data.extend(2i32.to_le_bytes());
+        data.extend(1i64.to_le_bytes());
+        push_string(&mut data, "TestRef");
+        push_string(&mut data, "Test");
+        push_string(&mut data, "Assembly-CSharp");
+        data.extend(42i32.to_le_bytes());
+        data.extend((-2i64).to_le_bytes());
+        for _ in 0..3 {
+            push_string(&mut data, "");
+        }
+
+        let obj = read_object(type_fields, data);
+        assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), 5);
+        assert_eq!(
+            i32::try_cast_from(&obj, "/Base/references/RefIds/Array/0/data/m_X").unwrap(),
+            42
+        );
+        assert_eq!(
+            i64::try_cast_from(&obj, "/Base/references/RefIds/Array/1/rid").unwrap(),
+            -2
+        );
+        assert!(i32::try_cast_from(&obj, "/Base/references/RefIds/Array/1/data/m_X").is_err());
+    }
+
+    #[test]
+    fn test_managed_references_registry_version1() {
+        let mut type_fields = Vec::new();
+        push_field(&mut type_fields, 0, "MonoBehaviour", "Base", -1, 0, 0);
+        push_field(
+            &mut type_fields,
+            1,
+            "ManagedReferencesRegistry",
+            "references",
+            -1,
+            4,
+            0,
+        );
+        push_field(&mut type_fields, 2, "int", "version", 4, 0, 0);
+        push_referenced_object(&mut type_fields, 2, "00000000", false);
+        push_field(&mut type_fields, 1, "int", "m_Value", 4, 0, 0);
+
+        let mut data = Vec::new();
+        data.extend(1i32.to_le_bytes());
+        for value in [7i32, 9] {
+            push_string(&mut data, "TestRef");
+            push_string(&mut data, "Test");
+            push_string(&mut data, "Assembly-CSharp");
+            data.extend(value.to_le_bytes());
+        }
+        push_string(&mut data, "Terminus");
+        push_string(&mut data, "UnityEngine.DMAT");
+        push_string(&mut data, "FAKE_ASM");
+        data.extend(5i32.to_le_bytes());
+
+        let obj = read_object(type_fields, data);
+        assert_eq!(
+            i32::try_cast_from(&obj, "/Base/references/00000001/data/m_X").unwrap(),
+            9
+        );
+        assert!(obj.get_field_by_path("/Base/references/00000002").is_err());
+        assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_managed_references_registry_version1_empty() {
+        let mut type_fields = Vec::new();
+        push_field(&mut type_fields, 0, "MonoBehaviour", "Base", -1, 0, 0);
+        push_field(
+            &mut type_fields,
+            1,
+            "ManagedReferencesRegistry",
+            "references",
+            -1,
+            4,
+            0,
+        );
+        push_field(&mut type_fields, 2, "int", "version", 4, 0, 0);
+        push_referenced_object(&mut type_fields, 2, "00000000", false);
+        push_field(&mut type_fields, 1, "int", "m_Value", 4, 0, 0);
+
+        // the terminator alone, no reference is stored.
+        let mut data = Vec::new();
+        data.extend(1i32.to_le_bytes());
+        push_string(&mut data, "Terminus");
+        push_string(&mut data, "UnityEngine.DMAT");
+        push_string(&mut data, "FAKE_ASM");
+        data.extend(5i32.to_le_bytes());
+
+        let obj = read_object(type_fields, data);
+        assert!(obj.get_field_by_path("/Base/references/00000001").is_err());
+        assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_managed_reference_type_not_found() {
+        let mut type_fields = Vec::new();
+        push_field(&mut type_fields, 0, "MonoBehaviour", "Base", -1, 0, 0);
+        push_referenced_object(&mut type_fields, 1, "reference", true);
+        push_field(&mut type_fields, 1, "int", "m_Value", 4, 0, 0);
+
+        let mut data = Vec::new();
+        data.extend(1i64.to_le_bytes());
+        push_string(&mut data, "MissingRef");
+        push_string(&mut data, "Test");
+        push_string(&mut data, "Assembly-CSharp");
+        data.extend(42i32.to_le_bytes());
+        data.extend(5i32.to_le_bytes());
+
+        let class_args =
+            TypeTreeObjectBinReadClassArgs::new(114, type_fields).with_ref_types(get_ref_types());
+        let err = TypeTreeObject::read_options(
+            &mut Cursor::new(data),
+            Endian::Little,
+            TypeTreeObjectBinReadArgs::new(0, 1, class_args),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("MissingRef"), "{err}");
+    }
+}
@@ -1,8 +1,13 @@
+use super::provider::{MemoryTypeTreeProvider, TypeTreeProvider};
 use super::{reader::TypeTreeObjectBinReadClassArgs, TypeField};
 use crate::error::{Error, ReadResult};
+use crate::serialized_file::SerializedFile;
 use crate::unityfs::UnityResource;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tar::Archive;
 
@@ -81,6 +86,10 @@ impl TypeField for TypeTreeNode {
         self.type_flags & 1 > 0
     }
 
+    fn get_type_flags(&self) -> u8 {
+        self.type_flags
+    }
+
     fn get_byte_size(&self) -> i32 {
         self.byte_size
     }
@@ -110,13 +119,171 @@ impl TypeField for TypeTreeNode {
     }
 }
 
-static INFO_JSON_TAR_READER: Lazy<Mutex<Option<Box<dyn UnityResource + Send + Sync>>>> =
-    Lazy::new(|| Mutex::new(None));
-static INFO_JSON_CACHE_MAP: Lazy<Mutex<HashMap<String, InfoJson::InfoJson>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
-static TYPE_TREE_OBJECT_BIN_READ_CLASS_ARGS_CACHE_MAP: Lazy<
-    Mutex<HashMap<String, BTreeMap<i32, TypeTreeObjectBinReadClassArgs>>>,
-> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Provider read type trees from InfoJson files, which can be a tar zstd compressed file,
+/// a directory of x.x.x.json files or a single json file.
+/// Loaded json files and type trees are cached in the provider.
+pub struct InfoJsonTypeTreeProvider {
+    source: Mutex<InfoJsonSource>,
+    /// Each version is loaded once, lookups of the version wait for the load.
+    info_json_cache_map: Mutex<HashMap<String, Arc<OnceCell<Option<InfoJson::InfoJson>>>>>,
+    class_args_cache: MemoryTypeTreeProvider,
+    /// Type trees added from files, used for classes not found in json files.
+    added_class_args: MemoryTypeTreeProvider,
+}
+
+enum InfoJsonSource {
+    None,
+    TarZst(Box<dyn UnityResource + Send + Sync>),
+    Dir(PathBuf),
+}
+
+static DEFAULT_INFO_JSON_PROVIDER: Lazy<Arc<InfoJsonTypeTreeProvider>> =
+    Lazy::new(|| Arc::new(InfoJsonTypeTreeProvider::new()));
+
+impl InfoJsonTypeTreeProvider {
+    /// Provider without json files, only type trees added by
+    /// [`TypeTreeProvider::add_type_object_args`] are used.
+    pub fn new() -> Self {
+        Self::with_source(InfoJsonSource::None)
+    }
+
+    /// see [`set_info_json_tar_reader`] for the file.
+    pub fn from_tar_zst(reader: Box<dyn UnityResource + Send + Sync>) -> Self {
+        Self::with_source(InfoJsonSource::TarZst(reader))
+    }
+
+    /// The directory contain files like x.x.x.json.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Self {
+        Self::with_source(InfoJsonSource::Dir(dir.as_ref().to_path_buf()))
+    }
+
+    /// The single json file is read at once, and only used for the version in it.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> ReadResult<Self> {
+        let info_json = read_info_json_from_file(path.as_ref())?;
+        let provider = Self::new();
+        if let Ok(mut info_json_cache_map) = provider.info_json_cache_map.lock() {
+            info_json_cache_map.insert(
+                info_json.Version.clone(),
+                Arc::new(OnceCell::with_value(Some(info_json))),
+            );
+        }
+        Ok(provider)
+    }
+
+    fn with_source(source: InfoJsonSource) -> Self {
+        Self {
+            source: Mutex::new(source),
+            info_json_cache_map: Mutex::new(HashMap::new()),
+            class_args_cache: MemoryTypeTreeProvider::new(),
+            added_class_args: MemoryTypeTreeProvider::new(),
+        }
+    }
+
+    /// Replace the tar zstd compressed file, versions not found before are read again.
+    pub fn set_tar_zst_reader(&self, reader: Box<dyn UnityResource + Send + Sync>) {
+        if let Ok(mut source) = self.source.lock() {
+            *source = InfoJsonSource::TarZst(reader);
+        }
+        if let Ok(mut info_json_cache_map) = self.info_json_cache_map.lock() {
+            info_json_cache_map.retain(|_, info_json| matches!(info_json.get(), Some(Some(_))));
+        }
+    }
+
+    fn read_info_json(&self, version: &str) -> ReadResult<Option<InfoJson::InfoJson>> {
+        let mut source = self
+            .source
+            .lock()
+            .map_err(|e| Error::Other(e.to_string()))?;
+        match &mut *source {
+            InfoJsonSource::None => Ok(None),
+            InfoJsonSource::TarZst(reader) => {
+                reader.seek(std::io::SeekFrom::Start(0))?;
+                read_info_json_from_tar(reader, version)
+            }
+            InfoJsonSource::Dir(dir) => {
+                let path = dir.join(format!("{version}.json"));
+                if !path.is_file() {
+                    return Ok(None);
+                }
+                read_info_json_from_file(&path).map(Some)
+            }
+        }
+    }
+
+    fn get_type_object_args_from_json(
+        &self,
+        version: &str,
+        class_id: i32,
+    ) -> Option<TypeTreeObjectBinReadClassArgs> {
+        // the json file is loaded without the cache map locked, reading from tar can take a while,
+        // so lookups of other versions are not blocked.
+        let info_json_cell = self
+            .info_json_cache_map
+            .lock()
+            .ok()?
+            .entry(version.to_owned())
+            .or_default()
+            .clone();
+        let info_json = info_json_cell
+            .get_or_init(|| self.read_info_json(version).ok().flatten())
+            .as_ref()?;
+        let class = info_json
+            .Classes
+            .iter()
+            .find(|class| class.TypeID == class_id)?;
+        let node = class.ReleaseRootNode.as_ref()?;
+        let read_args =
+            TypeTreeObjectBinReadClassArgs::new(class_id, get_type_fields_from_node(node));
+        self.class_args_cache
+            .add_type_object_args(version, vec![read_args.clone()]);
+        Some(read_args)
+    }
+}
+
+impl Default for InfoJsonTypeTreeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeTreeProvider for InfoJsonTypeTreeProvider {
+    fn get_type_object_args(
+        &self,
+        version: &str,
+        class_id: i32,
+    ) -> Option<TypeTreeObjectBinReadClassArgs> {
+        self.class_args_cache
+            .get_type_object_args(version, class_id)
+            .or_else(|| self.get_type_object_args_from_json(version, class_id))
+            .or_else(|| {
+                self.added_class_args
+                    .get_type_object_args(version, class_id)
+            })
+    }
+
+    fn has_type_object_args(&self, version: &str, class_id: i32) -> bool {
+        self.class_args_cache
+            .has_type_object_args(version, class_id)
+            || self
+                .added_class_args
+                .has_type_object_args(version, class_id)
+    }
+
+    /// Added type trees are only used for classes not found in the json files.
+    fn add_type_object_args(
+        &self,
+        version: &str,
+        class_args_list: Vec<TypeTreeObjectBinReadClassArgs>,
+    ) {
+        self.added_class_args
+            .add_type_object_args(version, class_args_list)
+    }
+}
+
+/// The process wide provider used by [`crate::unity_asset_view::UnityAssetViewer`] when no provider is set.
+pub fn get_default_info_json_provider() -> Arc<InfoJsonTypeTreeProvider> {
+    DEFAULT_INFO_JSON_PROVIDER.clone()
+}
 
 /// The tar zstd compressed file contain type tree info json files
 /// for read file without typetree info.
@@ -126,132 +293,397 @@ static TYPE_TREE_OBJECT_BIN_READ_CLASS_ARGS_CACHE_MAP: Lazy<
 /// or "tar -c InfoJson | zstd --ultra -22 -o InfoJson.tar.zst"  
 /// whitch can be less then 5MiB.
 /// contain file path like /InfoJson/x.x.x.json.
+///
+/// Set to the default provider, see [`get_default_info_json_provider`].
 pub fn set_info_json_tar_reader(reader: Box<dyn UnityResource + Send + Sync>) {
-    if let Ok(mut info_json_tar_reader) = INFO_JSON_TAR_READER.lock() {
-        *info_json_tar_reader = Some(reader)
-    }
+    DEFAULT_INFO_JSON_PROVIDER.set_tar_zst_reader(reader)
 }
 
-fn read_info_json_by_version(version: &String) -> ReadResult<InfoJson::InfoJson> {
-    if let Ok(mut info_json_tar_reader) = INFO_JSON_TAR_READER.lock() {
-        if let Some(ref mut info_json_tar_reader) = &mut *info_json_tar_reader {
-            info_json_tar_reader.seek(std::io::SeekFrom::Start(0))?;
-            let tar_reader = zstd::stream::read::Decoder::new(&mut *info_json_tar_reader)?;
-            let mut tar = Archive::new(tar_reader);
-
-            let json_path = format!("InfoJson/{version}.json");
-
-            for file in tar.entries()? {
-                let file = file?;
-
-                if let Some(path) = file.header().path()?.to_str() {
-                    if path == json_path {
-                        // files implement the Read trait
-                        let info_json: InfoJson::InfoJson = serde_json::from_reader(file)
-                            .map_err(|e| Error::Other(e.to_string()))?;
-                        return Ok(info_json);
-                    }
-                }
+fn read_info_json_from_file(path: &Path) -> ReadResult<InfoJson::InfoJson> {
+    let file = BufReader::new(File::open(path)?);
+    serde_json::from_reader(file).map_err(|e| Error::Other(e.to_string()))
+}
+
+fn read_info_json_from_tar<R: Read>(
+    reader: R,
+    version: &str,
+) -> ReadResult<Option<InfoJson::InfoJson>> {
+    let tar_reader = zstd::stream::read::Decoder::new(reader)?;
+    let mut tar = Archive::new(tar_reader);
+
+    let json_path = format!("InfoJson/{version}.json");
+
+    for file in tar.entries()? {
+        let file = file?;
+
+        if let Some(path) = file.header().path()?.to_str() {
+            if path == json_path {
+                // files implement the Read trait
+                let info_json: InfoJson::InfoJson =
+                    serde_json::from_reader(file).map_err(|e| Error::Other(e.to_string()))?;
+                return Ok(Some(info_json));
             }
         }
     }
-    Err(Error::Other(format!(
-        "cannot find json file for version {version:?}"
-    )))
+    Ok(None)
 }
 
-pub fn get_type_object_args_by_version_class_id(
-    version: &String,
-    class_id: i32,
-) -> Option<TypeTreeObjectBinReadClassArgs> {
-    if let Ok(type_tree_object_bin_read_class_args_cache_map) =
-        TYPE_TREE_OBJECT_BIN_READ_CLASS_ARGS_CACHE_MAP.lock()
-    {
-        if let Some(class_map) = type_tree_object_bin_read_class_args_cache_map.get(version) {
-            if let Some(read_args) = class_map.get(&class_id) {
-                return Some(read_args.clone());
-            }
+fn get_type_fields_from_node(node: &InfoJson::Node) -> Vec<Arc<Box<dyn TypeField + Send + Sync>>> {
+    fn get_nodes(type_tree_nodes: &mut Vec<TypeTreeNode>, node: &InfoJson::Node) {
+        let type_tree_node = TypeTreeNode {
+            version: node.Version,
+            level: node.Level,
+            type_flags: node.TypeFlags,
+            type_name: node.TypeName.clone(),
+            name: node.Name.clone(),
+            byte_size: node.ByteSize,
+            index: node.Index,
+            meta_flag: node.MetaFlag,
+        };
+        type_tree_nodes.push(type_tree_node);
+        for node in &node.SubNodes {
+            get_nodes(type_tree_nodes, node);
         }
     }
+    let mut type_tree_nodes = Vec::new();
+    get_nodes(&mut type_tree_nodes, node);
+    type_tree_nodes.sort_by(|ttna, ttnb| ttna.index.cmp(&ttnb.index));
+
+    type_tree_nodes
+        .into_iter()
+        .map(|ttn| Arc::new(Box::new(ttn) as Box<dyn TypeField + Send + Sync>))
+        .collect()
+}
 
-    if let Ok(mut info_json_cache_map) = INFO_JSON_CACHE_MAP.lock() {
-        let info_json = if let Some(info_json) = info_json_cache_map.get(version) {
-            info_json
-        } else {
-            let info_json = read_info_json_by_version(version).ok()?;
-            info_json_cache_map.insert(version.clone(), info_json);
-            info_json_cache_map.get(version).unwrap()
+fn get_node_from_type_fields(
+    type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+) -> Option<InfoJson::Node> {
+    fn get_node(
+        type_fields: &[Arc<Box<dyn TypeField + Send + Sync>>],
+        field_index: &mut usize,
+    ) -> InfoJson::Node {
+        let field = &type_fields[*field_index];
+        let mut node = InfoJson::Node {
+            TypeName: field.get_type().clone(),
+            Name: field.get_name().clone(),
+            Level: field.get_level(),
+            ByteSize: field.get_byte_size(),
+            // nodes are sorted by index when load, so use the position in type tree.
+            Index: *field_index as i32,
+            Version: field.get_version(),
+            TypeFlags: field.get_type_flags(),
+            MetaFlag: field.get_meta_flag(),
+            SubNodes: Vec::new(),
         };
+        while let Some(next_field) = type_fields.get(*field_index + 1) {
+            if next_field.get_level() <= field.get_level() {
+                break;
+            }
+            *field_index += 1;
+            node.SubNodes.push(get_node(type_fields, field_index));
+        }
+        node
+    }
+    type_fields.first()?;
+    Some(get_node(type_fields, &mut 0))
+}
 
-        if let Some(class) = info_json
-            .Classes
-            .iter()
-            .find(|class| class.TypeID == class_id)
+/// Add type trees of the version to the default provider, which are used for classes not found in the json files,
+/// see [`SerializedFile::get_embedded_type_object_args`].
+/// Type tree of class already added is not replaced.
+pub fn add_type_object_args_by_version(
+    version: &str,
+    class_args_list: impl IntoIterator<Item = TypeTreeObjectBinReadClassArgs>,
+) {
+    DEFAULT_INFO_JSON_PROVIDER.add_type_object_args(version, class_args_list.into_iter().collect())
+}
+
+pub fn has_type_object_args_by_version_class_id(version: &str, class_id: i32) -> bool {
+    DEFAULT_INFO_JSON_PROVIDER.has_type_object_args(version, class_id)
+}
+
+/// Collect type trees from files which has type tree,
+/// and write them as the InfoJson files read by [`InfoJsonTypeTreeProvider`].
+#[derive(Debug, Default)]
+pub struct InfoJsonExporter {
+    versions: BTreeMap<String, BTreeMap<i32, TypeTreeObjectBinReadClassArgs>>,
+}
+
+impl InfoJsonExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Type tree of class already added is not replaced.
+    pub fn add_type_object_args(
+        &mut self,
+        version: &str,
+        class_args: TypeTreeObjectBinReadClassArgs,
+    ) {
+        self.versions
+            .entry(version.to_owned())
+            .or_default()
+            .entry(class_args.get_class_id())
+            .or_insert(class_args);
+    }
+
+    pub fn add_serialized_file(&mut self, serialized_file: &SerializedFile) {
+        let version = serialized_file.get_unity_version();
+        if version.is_empty() {
+            return;
+        }
+        for class_args in serialized_file
+            .get_embedded_type_object_args()
+            .into_values()
         {
-            if let Some(node) = &class.ReleaseRootNode {
-                fn get_nodes(type_tree_nodes: &mut Vec<TypeTreeNode>, node: &InfoJson::Node) {
-                    let type_tree_node = TypeTreeNode {
-                        version: node.Version,
-                        level: node.Level,
-                        type_flags: node.TypeFlags,
-                        type_name: node.TypeName.clone(),
-                        name: node.Name.clone(),
-                        byte_size: node.ByteSize,
-                        index: node.Index,
-                        meta_flag: node.MetaFlag,
-                    };
-                    type_tree_nodes.push(type_tree_node);
-                    for node in &node.SubNodes {
-                        get_nodes(type_tree_nodes, node);
-                    }
-                }
-                let mut type_tree_nodes = Vec::new();
-                get_nodes(&mut type_tree_nodes, node);
-                type_tree_nodes.sort_by(|ttna, ttnb| ttna.index.cmp(&ttnb.index));
-
-                let type_fields = type_tree_nodes
-                    .into_iter()
-                    .map(|ttn| Arc::new(Box::new(ttn) as Box<dyn TypeField + Send + Sync>))
-                    .collect();
-                let read_args = TypeTreeObjectBinReadClassArgs::new(class_id, type_fields);
-
-                if let Ok(mut type_tree_object_bin_read_class_args_cache_map) =
-                    TYPE_TREE_OBJECT_BIN_READ_CLASS_ARGS_CACHE_MAP.lock()
-                {
-                    if let Some(class_map) =
-                        type_tree_object_bin_read_class_args_cache_map.get_mut(version)
-                    {
-                        class_map.insert(class_id, read_args.clone());
-                    } else {
-                        let mut class_map = BTreeMap::new();
-                        class_map.insert(class_id, read_args.clone());
-                        type_tree_object_bin_read_class_args_cache_map
-                            .insert(version.clone(), class_map);
-                    }
-                }
+            self.add_type_object_args(&version, class_args);
+        }
+    }
 
-                return Some(read_args);
-            }
+    pub fn get_versions(&self) -> Vec<&String> {
+        self.versions.keys().collect()
+    }
+
+    /// Write the InfoJson of version, which is the same as the json files in TypeTreeDumps.
+    pub fn write_json<W: Write>(&self, version: &str, writer: W) -> ReadResult<()> {
+        let class_map = self
+            .versions
+            .get(version)
+            .ok_or_else(|| Error::Other(format!("no type tree for version {version:?}")))?;
+        let info_json = InfoJson::InfoJson {
+            Version: version.to_owned(),
+            Strings: Vec::new(),
+            Classes: class_map
+                .values()
+                .filter_map(|class_args| {
+                    let node = get_node_from_type_fields(class_args.get_type_fields())?;
+                    Some(InfoJson::Class {
+                        Name: node.TypeName.clone(),
+                        FullName: node.TypeName.clone(),
+                        TypeID: class_args.get_class_id(),
+                        Size: node.ByteSize,
+                        ReleaseRootNode: Some(node),
+                        ..Default::default()
+                    })
+                })
+                .collect(),
+        };
+        serde_json::to_writer(writer, &info_json).map_err(|e| Error::Other(e.to_string()))
+    }
+
+    /// Write all versions to a tar zstd compressed file, contain file path like /InfoJson/x.x.x.json.
+    pub fn write_tar_zst<W: Write>(&self, writer: W) -> ReadResult<()> {
+        let encoder = zstd::stream::write::Encoder::new(writer, 19)?;
+        let mut tar = tar::Builder::new(encoder);
+        for version in self.versions.keys() {
+            let mut data = Vec::new();
+            self.write_json(version, &mut data)?;
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(
+                &mut header,
+                format!("InfoJson/{version}.json"),
+                data.as_slice(),
+            )?;
         }
+        tar.into_inner()?.finish()?;
+        Ok(())
     }
-    None
+}
+
+pub fn get_type_object_args_by_version_class_id(
+    version: &str,
+    class_id: i32,
+) -> Option<TypeTreeObjectBinReadClassArgs> {
+    DEFAULT_INFO_JSON_PROVIDER.get_type_object_args(version, class_id)
 }
 
 #[cfg(test)]
 mod test {
 
     use super::*;
+    use crate::serialized_file::test::{
+        build_serialized_file, build_serialized_file_with_type_tree,
+    };
+    use crate::type_tree::convert::TryCastFrom;
+    use std::io::{Cursor, Seek, SeekFrom};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_export_info_json() {
+        let version = "2020.3.0f1";
+        let stripped_data = build_serialized_file_with_type_tree(22, &[7, -3], false);
+        let read_stripped = |provider: Arc<InfoJsonTypeTreeProvider>| {
+            SerializedFile::read_with_type_tree_provider(
+                Box::new(Cursor::new(stripped_data.clone())),
+                0,
+                None,
+                Some(provider),
+            )
+            .unwrap()
+        };
+        let assert_values = |serialized_file: &SerializedFile| {
+            for (path_id, value) in [(1, 7), (2, -3)] {
+                let obj = serialized_file
+                    .get_tt_object_by_path_id(path_id)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), value);
+            }
+        };
+
+        // a file without type tree has nothing to export.
+        let mut exporter = InfoJsonExporter::new();
+        exporter.add_serialized_file(&read_stripped(Arc::new(InfoJsonTypeTreeProvider::new())));
+        assert!(exporter.get_versions().is_empty());
+
+        let data = build_serialized_file(22, &[1]);
+        let serialized_file = SerializedFile::read_with_type_tree_provider(
+            Box::new(Cursor::new(data)),
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        exporter.add_serialized_file(&serialized_file);
+        assert_eq!(exporter.get_versions(), vec![version]);
+        let mut tar_zst = Vec::new();
+        exporter.write_tar_zst(&mut tar_zst).unwrap();
+
+        // the exported type trees decode the objects of the stripped file.
+        let provider = Arc::new(InfoJsonTypeTreeProvider::from_tar_zst(Box::new(
+            Cursor::new(tar_zst),
+        )));
+        assert_values(&read_stripped(provider));
+
+        // type trees are only added as fallback on request.
+        let provider = Arc::new(InfoJsonTypeTreeProvider::new());
+        let data = build_serialized_file(22, &[1]);
+        let serialized_file = SerializedFile::read_with_type_tree_provider(
+            Box::new(Cursor::new(data)),
+            0,
+            None,
+            Some(provider.clone()),
+        )
+        .unwrap();
+        assert!(read_stripped(provider.clone())
+            .get_tt_object_by_path_id(1)
+            .is_err());
+        serialized_file.add_type_trees_to_provider();
+        assert_values(&read_stripped(provider));
+    }
 
     #[test]
-    fn test_json() {
-        let time = std::time::Instant::now();
-        let args = get_type_object_args_by_version_class_id(&"3.4.0".to_string(), 1);
-        println!("{:?}", args);
-        println!("Read use {:?}", time.elapsed());
-
-        let time = std::time::Instant::now();
-        let args = get_type_object_args_by_version_class_id(&"3.4.0".to_string(), 2);
-        println!("{:?}", args);
-        println!("Read use {:?}", time.elapsed());
+    fn test_info_json_provider() {
+        let data = build_serialized_file(22, &[7]);
+        let serialized_file = SerializedFile::read_with_type_tree_provider(
+            Box::new(Cursor::new(data)),
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        let version = "2020.3.0f1";
+        let mut exporter = InfoJsonExporter::new();
+        exporter.add_serialized_file(&serialized_file);
+        let class_args = serialized_file
+            .get_embedded_type_object_args()
+            .remove(&1000)
+            .unwrap();
+
+        let mut tar_zst = Vec::new();
+        exporter.write_tar_zst(&mut tar_zst).unwrap();
+        let dir = std::env::temp_dir().join(format!("io_unity_info_json_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join(format!("{version}.json"));
+        exporter
+            .write_json(version, File::create(&json_path).unwrap())
+            .unwrap();
+
+        let providers = [
+            InfoJsonTypeTreeProvider::from_tar_zst(Box::new(Cursor::new(tar_zst))),
+            InfoJsonTypeTreeProvider::from_dir(&dir),
+            InfoJsonTypeTreeProvider::from_json_file(&json_path).unwrap(),
+        ];
+        let root_field = class_args.get_type_fields()[..1].to_vec();
+        for provider in providers {
+            assert!(!provider.has_type_object_args(version, 1000));
+            // json files are used before added type trees
+            provider.add_type_object_args(
+                version,
+                vec![
+                    TypeTreeObjectBinReadClassArgs::new(1000, root_field.clone()),
+                    TypeTreeObjectBinReadClassArgs::new(1001, root_field.clone()),
+                ],
+            );
+            let class_args = provider.get_type_object_args(version, 1000).unwrap();
+            assert_eq!(class_args.get_type_fields().len(), 2);
+            let class_args = provider.get_type_object_args(version, 1001).unwrap();
+            assert_eq!(class_args.get_type_fields().len(), 1);
+            assert!(provider.has_type_object_args(version, 1000));
+            assert!(provider.get_type_object_args(version, 1).is_none());
+            assert!(provider.get_type_object_args("3.4.0", 1000).is_none());
+        }
+        assert!(InfoJsonTypeTreeProvider::new()
+            .get_type_object_args(version, 1000)
+            .is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_info_json_load_once() {
+        /// Tar reader which count the loads, every load seek to the start.
+        struct CountingReader {
+            reader: Cursor<Vec<u8>>,
+            load_count: Arc<AtomicUsize>,
+        }
+
+        impl Read for CountingReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.reader.read(buf)
+            }
+        }
+
+        impl Seek for CountingReader {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                if pos == SeekFrom::Start(0) {
+                    self.load_count.fetch_add(1, Ordering::SeqCst);
+                }
+                self.reader.seek(pos)
+            }
+        }
+
+        let serialized_file = SerializedFile::read_with_type_tree_provider(
+            Box::new(Cursor::new(build_serialized_file(22, &[7]))),
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut exporter = InfoJsonExporter::new();
+        exporter.add_serialized_file(&serialized_file);
+        let mut tar_zst = Vec::new();
+        exporter.write_tar_zst(&mut tar_zst).unwrap();
+
+        let load_count = Arc::new(AtomicUsize::new(0));
+        let provider = InfoJsonTypeTreeProvider::from_tar_zst(Box::new(CountingReader {
+            reader: Cursor::new(tar_zst),
+            load_count: load_count.clone(),
+        }));
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    assert!(provider.get_type_object_args("2020.3.0f1", 1000).is_some());
+                });
+            }
+        });
+        assert_eq!(load_count.load(Ordering::SeqCst), 1);
+        // classes not in the json and missing versions are not loaded again.
+        assert!(provider.get_type_object_args("2020.3.0f1", 1).is_none());
+        assert!(provider.get_type_object_args("5.0.0f1", 1).is_none());
+        assert!(provider.get_type_object_args("5.0.0f1", 1).is_none());
+        assert_eq!(load_count.load(Ordering::SeqCst), 2);
     }
 }
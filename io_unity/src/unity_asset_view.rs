@@ -1,16 +1,29 @@
 use std::{
     collections::{BTreeMap, HashMap},
-    fs::OpenOptions,
     io::{BufReader, Cursor},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use walkdir::WalkDir;
 
+#[cfg(feature = "mmap")]
+use crate::mmap_resource::MmapResource;
+#[cfg(feature = "zip-archive")]
+use crate::zip_archive::ZipArchive;
 use crate::{
     classes::{p_ptr::PPtr, ClassIDType},
+    resource::{open_resource_file_with_mode, FileOpenMode},
     serialized_file::SerializedFile,
+    split_resource::SplitResource,
+    type_tree::provider::{
+        get_default_type_tree_provider, MemoryTypeTreeProvider, TypeTreeProvider,
+    },
+    type_tree::reader::TypeTreeObjectBinReadClassArgs,
     type_tree::TypeTreeObject,
+    unity_web_data::UnityWebData,
+    unityfs::codec::BlockCodecs,
+    unityfs::decryptor::Decryptor,
     unityfs::UnityFS,
     unityfs::UnityResource,
 };
@@ -18,7 +31,64 @@ use crate::{
     classes::{p_ptr::PPtrObject, SerializedFileRef},
     type_tree::TypeTreeObjectRef,
 };
-use crate::{error::ReadResult, type_tree::convert::TryCastFrom};
+use crate::{
+    error::{Error, ReadResult},
+    type_tree::convert::TryCastFrom,
+};
+
+/// How the serialized files inside bundles are loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BundleLoadMode {
+    /// Uncompress the whole serialized file into memory when the bundle is added.
+    #[default]
+    Memory,
+    /// Uncompress the blocks of serialized file on demand while objects are read.
+    Stream,
+}
+
+/// Provider of serialized files added to a viewer which collect type trees,
+/// type trees are added to the viewer and the type tree provider is looked up first.
+struct CollectingTypeTreeProvider {
+    type_tree_provider: Option<Arc<dyn TypeTreeProvider>>,
+    collected_type_trees: Arc<MemoryTypeTreeProvider>,
+}
+
+impl TypeTreeProvider for CollectingTypeTreeProvider {
+    fn get_type_object_args(
+        &self,
+        version: &str,
+        class_id: i32,
+    ) -> Option<TypeTreeObjectBinReadClassArgs> {
+        self.type_tree_provider
+            .as_ref()
+            .and_then(|provider| provider.get_type_object_args(version, class_id))
+            .or_else(|| {
+                self.collected_type_trees
+                    .get_type_object_args(version, class_id)
+            })
+    }
+
+    fn has_type_object_args(&self, version: &str, class_id: i32) -> bool {
+        self.collected_type_trees
+            .has_type_object_args(version, class_id)
+    }
+
+    fn add_type_object_args(
+        &self,
+        version: &str,
+        class_args_list: Vec<TypeTreeObjectBinReadClassArgs>,
+    ) {
+        self.collected_type_trees
+            .add_type_object_args(version, class_args_list)
+    }
+}
+
+/// File of a player `Data` dir, mapped files are read by [`SerializedFile::read_mmap`].
+enum DataFile {
+    Reader(Box<dyn UnityResource + Send + Sync>),
+    #[cfg(feature = "mmap")]
+    Mmap(MmapResource),
+}
 
 #[derive(Default)]
 pub struct UnityAssetViewer {
@@ -28,8 +98,25 @@ pub struct UnityAssetViewer {
     unity_fs_map: BTreeMap<i64, UnityFS>,
     unity_fs_count: i64,
     serialized_file_to_unity_fs_map: BTreeMap<i64, i64>,
+    unity_web_data_map: BTreeMap<i64, UnityWebData>,
+    unity_web_data_count: i64,
+    serialized_file_to_unity_web_data_map: BTreeMap<i64, i64>,
     pub container_maps: HashMap<String, Vec<(i64, TypeTreeObjectRef)>>,
     container_name_maps: HashMap<i64, HashMap<i64, String>>,
+    decryptor: Option<Arc<dyn Decryptor>>,
+    block_codecs: Arc<BlockCodecs>,
+    bundle_load_mode: BundleLoadMode,
+    verify_bundles: bool,
+    scan_archives: bool,
+    file_open_mode: FileOpenMode,
+    type_tree_provider: Option<Arc<dyn TypeTreeProvider>>,
+    collected_type_trees: Option<Arc<MemoryTypeTreeProvider>>,
+    #[cfg(feature = "zip-archive")]
+    zip_archive_map: BTreeMap<i64, ZipArchive>,
+    #[cfg(feature = "zip-archive")]
+    zip_archive_count: i64,
+    #[cfg(feature = "zip-archive")]
+    serialized_file_to_zip_archive_map: BTreeMap<i64, (i64, String)>,
 }
 
 impl UnityAssetViewer {
@@ -37,13 +124,83 @@ impl UnityAssetViewer {
         Self::default()
     }
 
+    /// Set the decryptor used for bundles added after this call.
+    pub fn set_decryptor(&mut self, decryptor: Arc<dyn Decryptor>) {
+        self.decryptor = Some(decryptor);
+    }
+
+    /// Set the codecs used to uncompress bundles added after this call,
+    /// the built-in codecs are used if not set.
+    pub fn set_block_codecs(&mut self, block_codecs: Arc<BlockCodecs>) {
+        self.block_codecs = block_codecs;
+    }
+
+    /// Set the load mode used for bundles added after this call.
+    pub fn set_bundle_load_mode(&mut self, bundle_load_mode: BundleLoadMode) {
+        self.bundle_load_mode = bundle_load_mode;
+    }
+
+    /// Verify storage blocks and nodes of bundles added after this call with [`UnityFS::verify`],
+    /// bundles fail to verify are rejected with [`Error::UnityFSIntegrityError`].
+    pub fn set_verify_bundles(&mut self, verify_bundles: bool) {
+        self.verify_bundles = verify_bundles;
+    }
+
+    /// Find archives anywhere in files found by [`UnityAssetViewer::read_bundle_dir`] and in zip files,
+    /// like [`UnityAssetViewer::add_bundle_files`], which read every byte of files without archive at the start.
+    /// By default only archives at the start of files are read.
+    pub fn set_scan_archives(&mut self, scan_archives: bool) {
+        self.scan_archives = scan_archives;
+    }
+
+    /// Set how files are opened by [`UnityAssetViewer::read_bundle_dir`],
+    /// [`UnityAssetViewer::read_data_dir`] and resource lookups after this call.
+    pub fn set_file_open_mode(&mut self, file_open_mode: FileOpenMode) {
+        self.file_open_mode = file_open_mode;
+    }
+
+    /// Set the type tree provider used for serialized files added after this call,
+    /// the process wide provider from [`get_default_type_tree_provider`] is used if not set.
+    pub fn set_type_tree_provider(&mut self, type_tree_provider: Arc<dyn TypeTreeProvider>) {
+        self.type_tree_provider = Some(type_tree_provider);
+    }
+
+    /// Collect type trees of serialized files added after this call into a provider of this viewer,
+    /// they are used for files of the same version without type tree when the type tree provider has none.
+    /// The type tree provider itself is never changed.
+    pub fn set_collect_type_trees(&mut self, collect_type_trees: bool) {
+        if !collect_type_trees {
+            self.collected_type_trees = None;
+        } else if self.collected_type_trees.is_none() {
+            self.collected_type_trees = Some(Arc::new(MemoryTypeTreeProvider::new()));
+        }
+    }
+
+    /// Type trees collected after [`UnityAssetViewer::set_collect_type_trees`].
+    pub fn get_collected_type_trees(&self) -> Option<&Arc<MemoryTypeTreeProvider>> {
+        self.collected_type_trees.as_ref()
+    }
+
     pub fn read_bundle_dir<P: AsRef<Path>>(&mut self, dir_path: P) -> ReadResult<()> {
         for entry in WalkDir::new(dir_path).into_iter().flatten() {
             if entry.file_type().is_file() {
-                let file = OpenOptions::new().read(true).open(entry.path())?;
-                let file = Box::new(BufReader::new(file));
-                let _unity_fs_id = self
-                    .add_bundle_file(
+                // split parts are joined when the first part is found.
+                let file: Box<dyn UnityResource + Send + Sync> =
+                    match SplitResource::get_split_base_path(entry.path()) {
+                        Some((base_path, 0)) => {
+                            match SplitResource::open_with_mode(base_path, self.file_open_mode) {
+                                Ok(Some(split_resource)) => Box::new(split_resource),
+                                _ => continue,
+                            }
+                        }
+                        Some(_) => continue,
+                        None => match self.file_open_mode.open(entry.path()) {
+                            Ok(file) => file,
+                            Err(_) => continue,
+                        },
+                    };
+                let _unity_fs_ids = self
+                    .add_found_bundle_files(
                         file,
                         Some(entry.path().parent().unwrap().to_string_lossy().to_string()),
                     )
@@ -58,17 +215,35 @@ impl UnityAssetViewer {
         bundle_file_reader: Box<dyn UnityResource + Send + Sync>,
         resource_search_path: Option<String>,
     ) -> ReadResult<i64> {
-        let unity_fs = UnityFS::read(bundle_file_reader, resource_search_path)?;
+        let unity_fs = UnityFS::read_with_block_codecs(
+            bundle_file_reader,
+            resource_search_path,
+            self.decryptor.clone(),
+            self.block_codecs.clone(),
+        )?;
+        self.add_unity_fs(unity_fs)
+    }
+
+    fn add_unity_fs(&mut self, unity_fs: UnityFS) -> ReadResult<i64> {
+        if self.verify_bundles {
+            unity_fs
+                .verify(true)
+                .map_err(Error::UnityFSIntegrityError)?;
+        }
         let unity_fs_id = self.unity_fs_count;
         self.unity_fs_count += 1;
         for cab_path in unity_fs.get_cab_path() {
-            let cab_buff = unity_fs.get_file_data_by_path(&cab_path)?;
-            let cab_buff_reader = Box::new(Cursor::new(cab_buff));
-            // let cab_buff_reader = Box::new(BufReader::new(
-            //     unity_fs
-            //         .get_file_reader_by_path(&cab_path)
-            //         .ok_or(Error::Other("can not get cab reader".to_owned()))?,
-            // ));
+            let cab_buff_reader: Box<dyn UnityResource + Send + Sync> = match self.bundle_load_mode
+            {
+                BundleLoadMode::Memory => {
+                    Box::new(Cursor::new(unity_fs.get_file_data_by_path(&cab_path)?))
+                }
+                BundleLoadMode::Stream => Box::new(BufReader::new(
+                    unity_fs
+                        .get_file_reader_by_path(&cab_path)
+                        .ok_or(Error::Other("can not get cab reader".to_owned()))?,
+                )),
+            };
 
             let serialized_file_id = self.add_serialized_file(cab_buff_reader, None)?;
             self.serialized_file_to_unity_fs_map
@@ -79,19 +254,132 @@ impl UnityAssetViewer {
         Ok(unity_fs_id)
     }
 
+    /// Add every UnityFS archive found in the file,
+    /// include archives with leading junk bytes and archives concatenated into one file.
+    pub fn add_bundle_files(
+        &mut self,
+        bundle_file_reader: Box<dyn UnityResource + Send + Sync>,
+        resource_search_path: Option<String>,
+    ) -> ReadResult<Vec<i64>> {
+        let bundle_file_reader = match &self.decryptor {
+            Some(decryptor) => decryptor.decrypt_resource(bundle_file_reader)?,
+            None => bundle_file_reader,
+        };
+        let mut unity_fs_ids = Vec::new();
+        for archive_reader in
+            UnityFS::scan_archives_with_block_codecs(bundle_file_reader, &self.block_codecs)?
+        {
+            let unity_fs = UnityFS::read_decrypted(
+                Box::new(archive_reader),
+                resource_search_path.clone(),
+                self.decryptor.clone(),
+                self.block_codecs.clone(),
+            )?;
+            unity_fs_ids.push(self.add_unity_fs(unity_fs)?);
+        }
+        Ok(unity_fs_ids)
+    }
+
+    /// Add archives of a file found in dir or zip, see [`UnityAssetViewer::set_scan_archives`].
+    fn add_found_bundle_files(
+        &mut self,
+        bundle_file_reader: Box<dyn UnityResource + Send + Sync>,
+        resource_search_path: Option<String>,
+    ) -> ReadResult<Vec<i64>> {
+        if self.scan_archives {
+            self.add_bundle_files(bundle_file_reader, resource_search_path)
+        } else {
+            self.add_bundle_file(bundle_file_reader, resource_search_path)
+                .map(|unity_fs_id| vec![unity_fs_id])
+        }
+    }
+
+    /// Add WebGL `UnityWebData1.0` file, the serialized files and bundles inside are added,
+    /// other files inside are used as resource files of the serialized files.
+    pub fn add_web_data_file(
+        &mut self,
+        web_data_file_reader: Box<dyn UnityResource + Send + Sync>,
+        resource_search_path: Option<String>,
+    ) -> ReadResult<i64> {
+        let unity_web_data =
+            UnityWebData::read(web_data_file_reader, resource_search_path.clone())?;
+        let unity_web_data_id = self.unity_web_data_count;
+        self.unity_web_data_count += 1;
+        for path in unity_web_data.get_file_paths() {
+            if let Some(mut file_reader) = unity_web_data.get_file_reader_by_path(&path) {
+                if SerializedFile::is_serialized_file(&mut file_reader) {
+                    let serialized_file_id = self
+                        .add_serialized_file(Box::new(file_reader), resource_search_path.clone())?;
+                    self.serialized_file_to_unity_web_data_map
+                        .insert(serialized_file_id, unity_web_data_id);
+                    self.cab_maps.insert(path, serialized_file_id);
+                } else if UnityFS::is_unity_fs(&mut file_reader) {
+                    self.add_bundle_file(Box::new(file_reader), resource_search_path.clone())?;
+                }
+            }
+        }
+        self.unity_web_data_map
+            .insert(unity_web_data_id, unity_web_data);
+        Ok(unity_web_data_id)
+    }
+
     pub fn add_serialized_file(
         &mut self,
         serialized_file_reader: Box<dyn UnityResource + Send + Sync>,
         resource_search_path: Option<String>,
+    ) -> ReadResult<i64> {
+        self.add_serialized_file_read_by(|serialized_file_id, type_tree_provider| {
+            SerializedFile::read_with_type_tree_provider(
+                serialized_file_reader,
+                serialized_file_id,
+                resource_search_path,
+                type_tree_provider,
+            )
+        })
+    }
+
+    /// Same as [`UnityAssetViewer::add_serialized_file`], objects are read by [`SerializedFile::read_mmap`].
+    #[cfg(feature = "mmap")]
+    pub fn add_mmap_serialized_file(
+        &mut self,
+        mmap_resource: MmapResource,
+        resource_search_path: Option<String>,
+    ) -> ReadResult<i64> {
+        self.add_serialized_file_read_by(|serialized_file_id, type_tree_provider| {
+            SerializedFile::read_mmap(
+                mmap_resource,
+                serialized_file_id,
+                resource_search_path,
+                type_tree_provider,
+            )
+        })
+    }
+
+    fn add_serialized_file_read_by(
+        &mut self,
+        read_serialized_file: impl FnOnce(
+            i64,
+            Option<Arc<dyn TypeTreeProvider>>,
+        ) -> binrw::BinResult<SerializedFile>,
     ) -> ReadResult<i64> {
         let serialized_file_id = self.serialized_file_count;
         self.serialized_file_count += 1;
 
-        let serialized_file = SerializedFile::read(
-            serialized_file_reader,
-            serialized_file_id,
-            resource_search_path,
-        )?;
+        let type_tree_provider = self
+            .type_tree_provider
+            .clone()
+            .or_else(get_default_type_tree_provider);
+        let type_tree_provider = match &self.collected_type_trees {
+            Some(collected_type_trees) => Some(Arc::new(CollectingTypeTreeProvider {
+                type_tree_provider,
+                collected_type_trees: collected_type_trees.clone(),
+            }) as Arc<dyn TypeTreeProvider>),
+            None => type_tree_provider,
+        };
+        let serialized_file = read_serialized_file(serialized_file_id, type_tree_provider)?;
+        if self.collected_type_trees.is_some() {
+            serialized_file.add_type_trees_to_provider();
+        }
         if let Ok(Some(asset_bundle)) = serialized_file.get_tt_object_by_path_id(1) {
             if let Ok(containers) = <HashMap<String, TypeTreeObjectRef>>::try_cast_from(
                 &asset_bundle.into(),
@@ -152,56 +440,121 @@ impl UnityAssetViewer {
     }
 
     pub fn read_data_dir<P: AsRef<Path>>(&mut self, data_dir_path: P) -> ReadResult<()> {
+        let file_open_mode = self.file_open_mode;
+        self.add_data_files(
+            |file_name| {
+                let path = data_dir_path.as_ref().join(file_name);
+                #[cfg(feature = "mmap")]
+                if file_open_mode == FileOpenMode::Mmap {
+                    if let Ok(mmap_resource) = MmapResource::open(&path) {
+                        return Some(DataFile::Mmap(mmap_resource));
+                    }
+                }
+                open_resource_file_with_mode(path, file_open_mode).map(DataFile::Reader)
+            },
+            Some(data_dir_path.as_ref().to_string_lossy().to_string()),
+        )?;
+        Ok(())
+    }
+
+    /// Add the serialized files of a player `Data` dir, which are opened by `open_data_file`.
+    fn add_data_files(
+        &mut self,
+        open_data_file: impl Fn(&str) -> Option<DataFile>,
+        resource_search_path: Option<String>,
+    ) -> ReadResult<Vec<i64>> {
+        let mut file_names = Vec::new();
         for i in 0..u8::MAX {
             let file_name = format!("level{i}");
-            if let Ok(file) = OpenOptions::new()
-                .read(true)
-                .open(data_dir_path.as_ref().join(&file_name))
-            {
-                let serialized_file_id = self.add_serialized_file(
-                    Box::new(BufReader::new(file)),
-                    Some(data_dir_path.as_ref().to_string_lossy().to_string()),
-                )?;
-                self.cab_maps.insert(file_name, serialized_file_id);
+            if let Some(file) = open_data_file(&file_name) {
+                file_names.push((file_name, file));
             } else {
                 break;
             }
         }
         for i in 0..u8::MAX {
             let file_name = format!("sharedassets{i}.assets");
-            if let Ok(file) = OpenOptions::new()
-                .read(true)
-                .open(data_dir_path.as_ref().join(&file_name))
-            {
-                let serialized_file_id = self.add_serialized_file(
-                    Box::new(BufReader::new(file)),
-                    Some(data_dir_path.as_ref().to_string_lossy().to_string()),
-                )?;
-                self.cab_maps.insert(file_name, serialized_file_id);
+            if let Some(file) = open_data_file(&file_name) {
+                file_names.push((file_name, file));
             } else {
                 break;
             }
         }
-
-        let file_names = [
+        for file_name in [
             "resources.assets",
             "globalgamemanagers.assets",
             "globalgamemanagers",
-        ];
-        for file_name in file_names {
-            if let Ok(file) = OpenOptions::new()
-                .read(true)
-                .open(data_dir_path.as_ref().join(file_name))
-            {
-                let serialized_file_id = self.add_serialized_file(
-                    Box::new(BufReader::new(file)),
-                    Some(data_dir_path.as_ref().to_string_lossy().to_string()),
-                )?;
-                self.cab_maps
-                    .insert(file_name.to_owned(), serialized_file_id);
+        ] {
+            if let Some(file) = open_data_file(file_name) {
+                file_names.push((file_name.to_owned(), file));
             }
         }
-        Ok(())
+
+        let mut serialized_file_ids = Vec::new();
+        for (file_name, file) in file_names {
+            let serialized_file_id = match file {
+                DataFile::Reader(file) => {
+                    self.add_serialized_file(file, resource_search_path.clone())?
+                }
+                #[cfg(feature = "mmap")]
+                DataFile::Mmap(mmap_resource) => {
+                    self.add_mmap_serialized_file(mmap_resource, resource_search_path.clone())?
+                }
+            };
+            self.cab_maps.insert(file_name, serialized_file_id);
+            serialized_file_ids.push(serialized_file_id);
+        }
+        Ok(serialized_file_ids)
+    }
+
+    /// Add APK, OBB or other zip file. The serialized files in the `assets/bin/Data` dir
+    /// and all bundles inside are added, resource files are searched in the same dir of zip.
+    ///
+    /// Stored entries are read in place, but deflated entries are uncompressed into memory as a whole
+    /// when they are opened, so every deflated serialized file and bundle is held in memory
+    /// while it is added, and deflated resource files every time they are looked up.
+    #[cfg(feature = "zip-archive")]
+    pub fn add_zip_file(
+        &mut self,
+        zip_file_reader: Box<dyn UnityResource + Send + Sync>,
+    ) -> ReadResult<i64> {
+        const DATA_DIR: &str = "assets/bin/Data/";
+
+        let zip_archive = ZipArchive::read(zip_file_reader)?;
+        let zip_archive_id = self.zip_archive_count;
+        self.zip_archive_count += 1;
+        let serialized_file_ids = self.add_data_files(
+            |file_name| {
+                zip_archive
+                    .open_resource_file(&format!("{DATA_DIR}{file_name}"))
+                    .map(DataFile::Reader)
+            },
+            None,
+        )?;
+        for serialized_file_id in serialized_file_ids {
+            self.serialized_file_to_zip_archive_map
+                .insert(serialized_file_id, (zip_archive_id, DATA_DIR.to_owned()));
+        }
+
+        for entry_path in zip_archive.get_file_paths() {
+            // split parts are joined when the first part is found.
+            let path = match SplitResource::get_split_base_path(&entry_path) {
+                Some((base_path, 0)) => base_path.to_string_lossy().into_owned(),
+                Some(_) => continue,
+                None => entry_path.clone(),
+            };
+            // only uncompress the head of deflated entries to check the signature.
+            let is_bundle = zip_archive
+                .get_file_head_by_path(&entry_path, 8)
+                .is_ok_and(|head| UnityFS::is_unity_fs(&mut Cursor::new(head)));
+            if is_bundle {
+                if let Some(file) = zip_archive.open_resource_file(&path) {
+                    let _unity_fs_ids = self.add_found_bundle_files(file, None).unwrap_or_default();
+                }
+            }
+        }
+        self.zip_archive_map.insert(zip_archive_id, zip_archive);
+        Ok(zip_archive_id)
     }
 
     pub fn get_serialized_file_by_path(&self, path: &String) -> Option<&SerializedFile> {
@@ -334,13 +687,44 @@ impl UnityAssetViewer {
         serialized_file_id: i64,
         path: &String,
     ) -> Option<Box<dyn UnityResource>> {
-        get_resource_file_by_path(
+        if let Some(unity_web_data) = self
+            .serialized_file_to_unity_web_data_map
+            .get(&serialized_file_id)
+            .and_then(|web_data_id| self.unity_web_data_map.get(web_data_id))
+        {
+            let file_name = PathBuf::from(path)
+                .file_name()
+                .map(|f| f.to_string_lossy().into_owned());
+            if let Some(file_reader) = unity_web_data
+                .get_file_reader_by_path(path)
+                .or_else(|| unity_web_data.get_file_reader_by_path(&file_name?))
+            {
+                return Some(Box::new(file_reader));
+            }
+        }
+        #[cfg(feature = "zip-archive")]
+        if let Some(((zip_archive, dir), file_name)) = self
+            .serialized_file_to_zip_archive_map
+            .get(&serialized_file_id)
+            .and_then(|(zip_archive_id, dir)| {
+                Some((self.zip_archive_map.get(zip_archive_id)?, dir))
+            })
+            .zip(PathBuf::from(path).file_name())
+        {
+            if let Some(file_reader) =
+                zip_archive.open_resource_file(&format!("{dir}{}", file_name.to_string_lossy()))
+            {
+                return Some(file_reader);
+            }
+        }
+        get_resource_file_by_path_with_mode(
             path,
             self.serialized_file_map.get(&serialized_file_id),
             self.serialized_file_to_unity_fs_map
                 .get(&serialized_file_id)
                 .and_then(|fs_id| self.unity_fs_map.get(fs_id)),
             None,
+            self.file_open_mode,
         )
     }
 }
@@ -350,6 +734,23 @@ pub fn get_resource_file_by_path(
     serialized_file: Option<&SerializedFile>,
     unityfs: Option<&UnityFS>,
     search_path: Option<&String>,
+) -> Option<Box<dyn UnityResource>> {
+    get_resource_file_by_path_with_mode(
+        path,
+        serialized_file,
+        unityfs,
+        search_path,
+        FileOpenMode::default(),
+    )
+}
+
+/// Same as [`get_resource_file_by_path`], but open files with `file_open_mode`.
+pub fn get_resource_file_by_path_with_mode(
+    path: &String,
+    serialized_file: Option<&SerializedFile>,
+    unityfs: Option<&UnityFS>,
+    search_path: Option<&String>,
+    file_open_mode: FileOpenMode,
 ) -> Option<Box<dyn UnityResource>> {
     if let Some(file_name) = PathBuf::from(&path)
         .file_name()
@@ -364,32 +765,160 @@ pub fn get_resource_file_by_path(
         } else {
             if let Some(search_path) = search_path {
                 let path = PathBuf::from(search_path).join(&file_name);
-                if let Ok(file) = OpenOptions::new().read(true).open(path) {
-                    return Some(Box::new(BufReader::new(file)));
+                if let Some(file) = open_resource_file_with_mode(path, file_open_mode) {
+                    return Some(file);
                 }
             }
             if let Some(serialized_file) = serialized_file {
                 if let Some(search_path) = &serialized_file.resource_search_path {
                     let path = PathBuf::from(search_path).join(&file_name);
-                    if let Ok(file) = OpenOptions::new().read(true).open(path) {
-                        return Some(Box::new(BufReader::new(file)));
+                    if let Some(file) = open_resource_file_with_mode(path, file_open_mode) {
+                        return Some(file);
                     }
                 }
             }
             if let Some(unityfs) = unityfs {
                 if let Some(search_path) = &unityfs.resource_search_path {
                     let path = PathBuf::from(search_path).join(&file_name);
-                    if let Ok(file) = OpenOptions::new().read(true).open(path) {
-                        return Some(Box::new(BufReader::new(file)));
+                    if let Some(file) = open_resource_file_with_mode(path, file_open_mode) {
+                        return Some(file);
                     }
                 }
             }
             let path = PathBuf::from(".").join(&file_name);
-            if let Ok(file) = OpenOptions::new().read(true).open(path) {
-                return Some(Box::new(BufReader::new(file)));
+            if let Some(file) = open_resource_file_with_mode(path, file_open_mode) {
+                return Some(file);
             }
         }
     }
 
     None
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serialized_file::test::{
+        build_serialized_file, build_serialized_file_with_type_tree,
+    };
+
+    #[test]
+    fn test_collect_type_trees() {
+        let version = "2020.3.0f1";
+        let stripped = build_serialized_file_with_type_tree(22, &[7], false);
+        for collect_type_trees in [false, true] {
+            let mut viewer = UnityAssetViewer::new();
+            viewer.set_collect_type_trees(collect_type_trees);
+            viewer
+                .add_serialized_file(Box::new(Cursor::new(build_serialized_file(22, &[9]))), None)
+                .unwrap();
+            let stripped_id = viewer
+                .add_serialized_file(Box::new(Cursor::new(stripped.clone())), None)
+                .unwrap();
+            let obj = viewer.serialized_file_map[&stripped_id].get_tt_object_by_path_id(1);
+            if collect_type_trees {
+                let obj = obj.unwrap().unwrap();
+                assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), 7);
+                assert!(viewer
+                    .get_collected_type_trees()
+                    .unwrap()
+                    .has_type_object_args(version, 1000));
+            } else {
+                assert!(obj.is_err());
+                assert!(viewer.get_collected_type_trees().is_none());
+            }
+            // the process wide provider is not changed by viewers.
+            assert!(get_default_type_tree_provider()
+                .is_none_or(|provider| !provider.has_type_object_args(version, 1000)));
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_read_data_dir_mmap() {
+        let dir = std::env::temp_dir().join(format!("io_unity_data_mmap_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("level0"), build_serialized_file(22, &[7])).unwrap();
+        let mut viewer = UnityAssetViewer::new();
+        viewer.set_file_open_mode(FileOpenMode::Mmap);
+        viewer.read_data_dir(&dir).unwrap();
+        let obj = viewer
+            .get_serialized_file_by_path(&"level0".to_owned())
+            .unwrap()
+            .get_tt_object_by_path_id(1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), 7);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "zip-archive")]
+    #[test]
+    fn test_add_zip_file() {
+        use crate::unityfs::{writer::UnityFSBuilder, NODE_FLAG_SERIALIZED_FILE};
+        use crate::zip_archive::test::build_zip;
+        use crate::zip_archive::{COMPRESSION_METHOD_DEFLATED, COMPRESSION_METHOD_STORED};
+        use std::io::Read;
+
+        let level_ress: Vec<u8> = (0..3000u32).map(|i| (i % 7) as u8).collect();
+        let bundle_ress: Vec<u8> = (0..2000u32).map(|i| (i % 5) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        UnityFSBuilder::new()
+            .add_node(
+                "CAB-b",
+                NODE_FLAG_SERIALIZED_FILE,
+                Box::new(Cursor::new(build_serialized_file(22, &[9]))),
+            )
+            .add_node("CAB-b.resS", 0, Box::new(Cursor::new(bundle_ress.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let zip = build_zip(&[
+            (
+                "assets/bin/Data/level0",
+                COMPRESSION_METHOD_DEFLATED,
+                &build_serialized_file(22, &[7]),
+            ),
+            (
+                "assets/bin/Data/level0.resS",
+                COMPRESSION_METHOD_DEFLATED,
+                &level_ress,
+            ),
+            (
+                "assets/aa/b.bundle",
+                COMPRESSION_METHOD_STORED,
+                bundle.get_ref(),
+            ),
+        ]);
+
+        let mut viewer = UnityAssetViewer::new();
+        viewer.add_zip_file(Box::new(Cursor::new(zip))).unwrap();
+        for (path, ress_path, value, ress) in [
+            ("level0", "level0.resS", 7, &level_ress),
+            ("CAB-b", "archive:/CAB-b/CAB-b.resS", 9, &bundle_ress),
+        ] {
+            let serialized_file_id = viewer.cab_maps[path];
+            let obj = viewer.serialized_file_map[&serialized_file_id]
+                .get_tt_object_by_path_id(1)
+                .unwrap()
+                .unwrap();
+            assert_eq!(i32::try_cast_from(&obj, "/Base/m_Value").unwrap(), value);
+            // resource files are looked up in the Data dir of zip and in the bundle.
+            let mut data = Vec::new();
+            viewer
+                .get_resource_file_by_serialized_file_id_and_path(
+                    serialized_file_id,
+                    &ress_path.to_owned(),
+                )
+                .unwrap()
+                .read_to_end(&mut data)
+                .unwrap();
+            assert_eq!(&data, ress);
+        }
+        assert!(viewer
+            .get_resource_file_by_serialized_file_id_and_path(
+                viewer.cab_maps["level0"],
+                &"missing.resS".to_owned(),
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::unityfs::{ResourceWindow, UnityResource};
+use binrw::{binrw, BinRead, BinResult, Endian};
+use std::io::{prelude::*, ErrorKind, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+/// The `UnityWebData1.0` container used by WebGL builds to pack
+/// `data.unity3d`, `globalgamemanagers`, `.resS` files and so on into one `*.data` file.
+#[derive(Clone)]
+pub struct UnityWebData {
+    content: UnityWebDataFile,
+    file_reader: Arc<Mutex<Box<dyn UnityResource + Send>>>,
+    pub resource_search_path: Option<String>,
+}
+
+#[binrw]
+#[brw(little, magic = b"UnityWebData1.0\0")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnityWebDataFile {
+    head_length: u32,
+    #[br(parse_with = nodes_parser, args (head_length,))]
+    directory_info: Vec<Node>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    offset: u32,
+    size: u32,
+    path_length: u32,
+    #[br(count = path_length)]
+    path: Vec<u8>,
+}
+
+impl Node {
+    pub fn path(&self) -> String {
+        String::from_utf8_lossy(&self.path).into_owned()
+    }
+}
+
+fn nodes_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    args: (u32,),
+) -> BinResult<Vec<Node>> {
+    let (head_length,) = args;
+    let mut nodes = Vec::new();
+    while reader.stream_position()? < head_length as u64 {
+        nodes.push(Node::read_options(reader, endian, ())?);
+    }
+    Ok(nodes)
+}
+
+impl UnityWebData {
+    pub fn get_file_paths(&self) -> Vec<String> {
+        self.content
+            .directory_info
+            .iter()
+            .map(|node| node.path())
+            .collect()
+    }
+
+    pub fn get_file_reader_by_path(&self, path: &String) -> Option<ResourceWindow> {
+        self.content
+            .directory_info
+            .iter()
+            .find(|node| path == &node.path())
+            .map(|node| {
+                ResourceWindow::new(
+                    self.file_reader.clone(),
+                    node.offset as u64,
+                    node.size as u64,
+                )
+            })
+    }
+
+    pub fn get_file_data_by_path(&self, path: &String) -> std::io::Result<Vec<u8>> {
+        let mut file_reader = self
+            .get_file_reader_by_path(path)
+            .ok_or(std::io::Error::from(ErrorKind::NotFound))?;
+        let mut file_data = Vec::new();
+        file_reader.read_to_end(&mut file_data)?;
+        Ok(file_data)
+    }
+
+    /// Read `UnityWebData1.0` file.
+    /// With `webgl-compression` feature, gzip and brotli compressed files (`.unityweb`)
+    /// are uncompressed into memory before read.
+    pub fn read(
+        file: Box<dyn UnityResource + Send>,
+        resource_search_path: Option<String>,
+    ) -> BinResult<UnityWebData> {
+        let mut file = uncompress_web_data(file)?;
+        let content = UnityWebDataFile::read(&mut file)?;
+        Ok(UnityWebData {
+            content,
+            file_reader: Arc::new(Mutex::new(file)),
+            resource_search_path,
+        })
+    }
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const BROTLI_MAGIC: &[u8; 6] = b"brotli";
+const BROTLI_MAGIC_OFFSET: u64 = 0x20;
+
+/// Unwrap the gzip or brotli compression unity use for WebGL build files.
+/// Unity brotli compressed files are recognized by the `UnityWeb Compressed Content (brotli)`
+/// comment it writes into the stream.
+/// Uncompressed files are returned as is.
+pub fn uncompress_web_data(
+    mut file: Box<dyn UnityResource + Send>,
+) -> std::io::Result<Box<dyn UnityResource + Send>> {
+    let mut magic = [0u8; 2];
+    file.seek(SeekFrom::Start(0))?;
+    let is_gzip = file.read_exact(&mut magic).is_ok() && magic == GZIP_MAGIC;
+    let mut magic = [0u8; 6];
+    file.seek(SeekFrom::Start(BROTLI_MAGIC_OFFSET))?;
+    let is_brotli = file.read_exact(&mut magic).is_ok() && &magic == BROTLI_MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+
+    if !(is_gzip || is_brotli) {
+        return Ok(file);
+    }
+
+    #[cfg(feature = "webgl-compression")]
+    {
+        let mut uncompressed = Vec::new();
+        if is_gzip {
+            flate2::read::GzDecoder::new(file).read_to_end(&mut uncompressed)?;
+        } else {
+            brotli_decompressor::Decompressor::new(file, 4096).read_to_end(&mut uncompressed)?;
+        }
+        Ok(Box::new(std::io::Cursor::new(uncompressed)))
+    }
+
+    #[cfg(not(feature = "webgl-compression"))]
+    Err(std::io::Error::new(
+        ErrorKind::Unsupported,
+        "compressed web data need webgl-compression feature",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use binrw::BinWrite;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_unity_web_data() {
+        let files: [(&str, Vec<u8>); 2] = [
+            ("data.unity3d", (0..300u32).map(|i| i as u8).collect()),
+            ("Resources/unity_builtin_extra", vec![7u8; 40]),
+        ];
+        let head_length = 16 + 4 + files.iter().map(|(p, _)| 12 + p.len() as u32).sum::<u32>();
+        let mut content = UnityWebDataFile {
+            head_length,
+            directory_info: Vec::new(),
+        };
+        let mut offset = head_length;
+        for (path, data) in &files {
+            content.directory_info.push(Node {
+                offset,
+                size: data.len() as u32,
+                path_length: path.len() as u32,
+                path: path.as_bytes().to_vec(),
+            });
+            offset += data.len() as u32;
+        }
+        let mut web_data = Cursor::new(Vec::new());
+        content.write(&mut web_data).unwrap();
+        for (_, data) in &files {
+            web_data.write_all(data).unwrap();
+        }
+
+        let web_data = UnityWebData::read(Box::new(web_data), None).unwrap();
+        assert_eq!(
+            web_data.get_file_paths(),
+            vec![
+                "data.unity3d".to_owned(),
+                "Resources/unity_builtin_extra".to_owned()
+            ]
+        );
+        for (path, data) in &files {
+            assert_eq!(
+                &web_data.get_file_data_by_path(&path.to_string()).unwrap(),
+                data
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Default budget of uncompressed block data kept by every UnityFS.
+pub const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 32 * 1024 * 1024;
+
+/// Size bounded LRU cache of uncompressed storage blocks, keyed by block index.
+pub struct BlockCache {
+    capacity: usize,
+    size: usize,
+    tick: u64,
+    blocks: HashMap<usize, (Arc<Vec<u8>>, u64)>,
+    lru: BTreeMap<u64, usize>,
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_BLOCK_CACHE_CAPACITY)
+    }
+}
+
+impl BlockCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            tick: 0,
+            blocks: HashMap::new(),
+            lru: BTreeMap::new(),
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Total size of cached blocks.
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub fn get(&mut self, block_index: usize) -> Option<Arc<Vec<u8>>> {
+        let (block, last_used) = self.blocks.get_mut(&block_index)?;
+        self.lru.remove(last_used);
+        self.tick += 1;
+        *last_used = self.tick;
+        self.lru.insert(self.tick, block_index);
+        Some(block.clone())
+    }
+
+    /// A block larger than the capacity is still kept as the only cached block,
+    /// so single block archives are not uncompressed on every read. Nothing is cached with capacity `0`.
+    pub fn insert(&mut self, block_index: usize, block: Arc<Vec<u8>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(block_index);
+        self.tick += 1;
+        self.size += block.len();
+        self.blocks.insert(block_index, (block, self.tick));
+        self.lru.insert(self.tick, block_index);
+        self.evict();
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.lru.clear();
+        self.size = 0;
+    }
+
+    fn remove(&mut self, block_index: usize) {
+        if let Some((block, last_used)) = self.blocks.remove(&block_index) {
+            self.lru.remove(&last_used);
+            self.size -= block.len();
+        }
+    }
+
+    /// Evict least recently used blocks, the most recent one is kept unless capacity is `0`.
+    fn evict(&mut self) {
+        while self.size > self.capacity && (self.capacity == 0 || self.lru.len() > 1) {
+            match self.lru.first_key_value() {
+                Some((_, &block_index)) => self.remove(block_index),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_block_cache() {
+        let mut cache = BlockCache::new(100);
+        cache.insert(0, Arc::new(vec![0u8; 40]));
+        cache.insert(1, Arc::new(vec![1u8; 40]));
+        assert!(cache.get(0).is_some());
+        // block 1 is the least recently used one.
+        cache.insert(2, Arc::new(vec![2u8; 40]));
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.get(0).unwrap()[0], 0);
+        assert_eq!(cache.get(2).unwrap()[0], 2);
+        assert_eq!(cache.get_size(), 80);
+
+        cache.set_capacity(50);
+        assert_eq!(cache.get_size(), 40);
+        assert!(cache.get(2).is_some());
+        assert!(cache.get(0).is_none());
+
+        // oversized block evicts everything else but is kept.
+        cache.insert(3, Arc::new(vec![3u8; 101]));
+        assert_eq!(cache.get_size(), 101);
+        assert!(cache.get(2).is_none());
+        assert_eq!(cache.get(3).unwrap()[0], 3);
+        cache.insert(4, Arc::new(vec![4u8; 10]));
+        assert!(cache.get(3).is_none());
+        assert_eq!(cache.get_size(), 10);
+
+        cache.set_capacity(0);
+        assert_eq!(cache.get_size(), 0);
+        cache.insert(5, Arc::new(vec![5u8; 10]));
+        assert!(cache.get(5).is_none());
+    }
+}
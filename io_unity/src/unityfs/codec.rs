@@ -0,0 +1,310 @@
+use super::CompressionType;
+use lz4::block::{compress, decompress, CompressionMode};
+use lzma_rs::decompress::UnpackedSize;
+use lzma_rs::{lzma_compress_with_options, lzma_decompress_with_options};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+/// Compressor and decompressor of storage blocks for one compression id.
+pub trait BlockCodec: Send + Sync {
+    fn decompress(
+        &self,
+        uncompressed_size: u64,
+        compressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>>;
+
+    /// Only needed when writing archives, codecs only used for reading can keep the default.
+    fn compress(&self, _uncompressed_data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "codec does not support compression",
+        ))
+    }
+}
+
+struct NoneCodec;
+
+impl BlockCodec for NoneCodec {
+    fn decompress(
+        &self,
+        _uncompressed_size: u64,
+        compressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        Ok(compressed_data)
+    }
+
+    fn compress(&self, uncompressed_data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        Ok(uncompressed_data)
+    }
+}
+
+struct LzmaCodec;
+
+impl BlockCodec for LzmaCodec {
+    fn decompress(
+        &self,
+        uncompressed_size: u64,
+        compressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        // unity lzma block only has 5 bytes properties header, without uncompressed size.
+        let mut uncompressed_data = Vec::with_capacity(uncompressed_size as usize);
+        lzma_decompress_with_options(
+            &mut compressed_data.as_slice(),
+            &mut uncompressed_data,
+            &lzma_rs::decompress::Options {
+                unpacked_size: UnpackedSize::UseProvided(Some(uncompressed_size)),
+                ..Default::default()
+            },
+        )
+        .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+        Ok(uncompressed_data)
+    }
+
+    fn compress(&self, uncompressed_data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let mut compressed_data = Vec::new();
+        lzma_compress_with_options(
+            &mut uncompressed_data.as_slice(),
+            &mut compressed_data,
+            &lzma_rs::compress::Options {
+                unpacked_size: lzma_rs::compress::UnpackedSize::SkipWritingToHeader,
+            },
+        )?;
+        Ok(compressed_data)
+    }
+}
+
+struct Lz4Codec {
+    /// Compression level of LZ4HC, plain LZ4 is used if `None`.
+    high_compression_level: Option<i32>,
+}
+
+impl BlockCodec for Lz4Codec {
+    fn decompress(
+        &self,
+        uncompressed_size: u64,
+        compressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        decompress(&compressed_data, Some(uncompressed_size as i32))
+    }
+
+    fn compress(&self, uncompressed_data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let compression_mode = match self.high_compression_level {
+            Some(level) => CompressionMode::HIGHCOMPRESSION(level),
+            None => CompressionMode::DEFAULT,
+        };
+        compress(&uncompressed_data, Some(compression_mode), false)
+    }
+}
+
+/// Codecs of storage blocks and blocks info, keyed by the 6 bits compression id
+/// of storage block and archive flags.
+///
+/// Every [`super::UnityFS`] keeps its own table, so codecs registered for one archive
+/// do not change how other archives are read.
+/// The built-in None/LZMA/LZ4/LZ4HC codecs are registered by default.
+#[derive(Clone)]
+pub struct BlockCodecs {
+    codecs: BTreeMap<u8, Arc<dyn BlockCodec>>,
+}
+
+static DEFAULT_BLOCK_CODECS: Lazy<Arc<BlockCodecs>> = Lazy::new(|| {
+    let mut codecs: BTreeMap<u8, Arc<dyn BlockCodec>> = BTreeMap::new();
+    codecs.insert(CompressionType::None as u8, Arc::new(NoneCodec));
+    codecs.insert(CompressionType::Lzma as u8, Arc::new(LzmaCodec));
+    codecs.insert(
+        CompressionType::Lz4 as u8,
+        Arc::new(Lz4Codec {
+            high_compression_level: None,
+        }),
+    );
+    codecs.insert(
+        CompressionType::Lz4HC as u8,
+        Arc::new(Lz4Codec {
+            high_compression_level: Some(9),
+        }),
+    );
+    Arc::new(BlockCodecs { codecs })
+});
+
+impl Default for BlockCodecs {
+    fn default() -> Self {
+        DEFAULT_BLOCK_CODECS.as_ref().clone()
+    }
+}
+
+impl BlockCodecs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table with only the built-in codecs, shared by archives read without their own table.
+    pub fn get_default() -> Arc<BlockCodecs> {
+        DEFAULT_BLOCK_CODECS.clone()
+    }
+
+    /// Register codec for the compression id, replace the built-in codec if the id is already registered.
+    /// Return the codec previously registered for the id.
+    pub fn register(
+        &mut self,
+        compression_id: u8,
+        codec: Arc<dyn BlockCodec>,
+    ) -> Option<Arc<dyn BlockCodec>> {
+        self.codecs.insert(compression_id & 0x3f, codec)
+    }
+
+    pub fn get(&self, compression_id: u8) -> Option<Arc<dyn BlockCodec>> {
+        self.codecs.get(&compression_id).cloned()
+    }
+
+    pub(super) fn block_uncompressed(
+        &self,
+        uncompressed_size: u64,
+        compression_id: u8,
+        compressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        self.get(compression_id)
+            .ok_or_else(|| unsupported_compression(compression_id))?
+            .decompress(uncompressed_size, compressed_data)
+    }
+
+    pub(super) fn block_compressed(
+        &self,
+        compression_id: u8,
+        uncompressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        self.get(compression_id)
+            .ok_or_else(|| unsupported_compression(compression_id))?
+            .compress(uncompressed_data)
+    }
+}
+
+fn unsupported_compression(compression_id: u8) -> std::io::Error {
+    std::io::Error::new(
+        ErrorKind::Unsupported,
+        format!("compression id {} is not supported", compression_id),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::StorageBlockFlags;
+    use super::*;
+
+    struct XorCodec;
+
+    impl BlockCodec for XorCodec {
+        fn decompress(
+            &self,
+            _uncompressed_size: u64,
+            compressed_data: Vec<u8>,
+        ) -> std::io::Result<Vec<u8>> {
+            Ok(compressed_data.into_iter().map(|b| b ^ 0x3c).collect())
+        }
+    }
+
+    #[test]
+    fn test_register_block_codec() {
+        let flags = StorageBlockFlags::from_bytes(42u16.to_le_bytes());
+        assert_eq!(flags.compression_id(), 42);
+        let mut block_codecs = BlockCodecs::new();
+        assert_eq!(
+            block_codecs
+                .block_uncompressed(3, flags.compression_id(), vec![1, 2, 3])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Unsupported
+        );
+
+        assert!(block_codecs.register(42, Arc::new(XorCodec)).is_none());
+        assert_eq!(
+            block_codecs
+                .block_uncompressed(3, flags.compression_id(), vec![0x3d, 0x3e, 0x3f])
+                .unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            block_codecs
+                .block_compressed(42, vec![1, 2, 3])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Unsupported
+        );
+        // the default table is not changed.
+        assert!(BlockCodecs::get_default().get(42).is_none());
+
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 7) as u8).collect();
+        for compression_type in [
+            CompressionType::None,
+            CompressionType::Lzma,
+            CompressionType::Lz4,
+            CompressionType::Lz4HC,
+        ] {
+            let compressed = block_codecs
+                .block_compressed(compression_type as u8, data.clone())
+                .unwrap();
+            assert_eq!(
+                block_codecs
+                    .block_uncompressed(data.len() as u64, compression_type as u8, compressed)
+                    .unwrap(),
+                data
+            );
+        }
+    }
+
+    /// Codec store blocks in reverse order, which can also compress.
+    struct ReverseCodec;
+
+    impl BlockCodec for ReverseCodec {
+        fn decompress(
+            &self,
+            _uncompressed_size: u64,
+            mut compressed_data: Vec<u8>,
+        ) -> std::io::Result<Vec<u8>> {
+            compressed_data.reverse();
+            Ok(compressed_data)
+        }
+
+        fn compress(&self, mut uncompressed_data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+            uncompressed_data.reverse();
+            Ok(uncompressed_data)
+        }
+    }
+
+    #[test]
+    fn test_block_codecs_per_archive() {
+        use super::super::{writer::UnityFSBuilder, UnityFS};
+        use std::io::Cursor;
+
+        let cab: Vec<u8> = (0..10000u32).map(|i| (i % 13) as u8).collect();
+        let mut block_codecs = BlockCodecs::new();
+        block_codecs.register(CompressionType::Lz4 as u8, Arc::new(ReverseCodec));
+        let block_codecs = Arc::new(block_codecs);
+        let mut bundle = Cursor::new(Vec::new());
+        UnityFSBuilder::new()
+            .block_compression(CompressionType::Lz4)
+            .blocks_info_compression(CompressionType::None)
+            .block_size(4096)
+            .block_codecs(block_codecs.clone())
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let bundle = bundle.into_inner();
+
+        let unity_fs = UnityFS::read_with_block_codecs(
+            Box::new(Cursor::new(bundle.clone())),
+            None,
+            None,
+            block_codecs,
+        )
+        .unwrap();
+        assert_eq!(
+            unity_fs.get_file_data_by_path(&"CAB-0".to_owned()).unwrap(),
+            cab
+        );
+        // archives read with the built-in codecs are not affected.
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle)), None).unwrap();
+        assert!(unity_fs.get_file_data_by_path(&"CAB-0".to_owned()).is_err());
+    }
+}
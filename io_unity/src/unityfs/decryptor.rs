@@ -0,0 +1,27 @@
+use super::UnityResource;
+
+/// Game specific decryption of obfuscated bundles.
+///
+/// A decryptor can be registered with [`super::UnityFS::read_with_decryptor`] or
+/// `UnityAssetViewer::set_decryptor`. Both methods have a default implementation
+/// which return the input unchanged, so a decryptor only implement the stage it need.
+pub trait Decryptor: Send + Sync {
+    /// Decrypt the whole resource, called before the archive header is read.
+    fn decrypt_resource(
+        &self,
+        file: Box<dyn UnityResource + Send>,
+    ) -> std::io::Result<Box<dyn UnityResource + Send>> {
+        Ok(file)
+    }
+
+    /// Decrypt the compressed data of the storage block at `block_index`,
+    /// called before the block is uncompressed.
+    fn decrypt_block(
+        &self,
+        _block_index: usize,
+        _compression_id: u8,
+        block_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        Ok(block_data)
+    }
+}
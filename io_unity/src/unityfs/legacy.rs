@@ -0,0 +1,288 @@
+use super::{
+    codec::{BlockCodec, BlockCodecs},
+    ArchiveFlags, BlocksInfo, CompressionType, Node, StorageBlock, StorageBlockFlags, UnityFSFile,
+};
+use binrw::{binrw, BinRead, BinResult, NullString};
+use std::io::{prelude::*, Cursor, ErrorKind, SeekFrom};
+use std::sync::Arc;
+
+/// Header of the unity 3.x/4.x era `UnityWeb` (lzma compressed) and `UnityRaw` bundles.
+#[binrw]
+#[brw(big)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnityWebFile {
+    signature: NullString,
+    version: u32,
+    unity_version: NullString,
+    unity_revision: NullString,
+    #[br(if(version >= 4))]
+    hash: Option<[u8; 16]>,
+    #[br(if(version >= 4))]
+    crc: Option<u32>,
+    minimum_streamed_bytes: u32,
+    header_size: u32,
+    number_of_levels_to_download_before_streaming: u32,
+    level_count: i32,
+    #[br(count = level_count)]
+    levels: Vec<LevelInfo>,
+    #[br(if(version >= 2))]
+    complete_file_size: Option<u32>,
+    #[br(if(version >= 3))]
+    file_info_header_size: Option<u32>,
+}
+
+#[binrw]
+#[derive(Clone, Debug, PartialEq)]
+struct LevelInfo {
+    compressed_size: u32,
+    uncompressed_size: u32,
+}
+
+#[binrw]
+#[brw(big)]
+#[derive(Clone, Debug, PartialEq)]
+struct DirectoryInfo {
+    nodes_count: i32,
+    #[br(count = nodes_count)]
+    nodes: Vec<LegacyNode>,
+}
+
+#[binrw]
+#[derive(Clone, Debug, PartialEq)]
+struct LegacyNode {
+    path: NullString,
+    offset: u32,
+    size: u32,
+}
+
+/// The data of `UnityWeb` bundle is a lzma-alone stream, which has the 8 bytes uncompressed size
+/// after the 5 bytes properties, unlike the lzma storage blocks of UnityFS.
+struct LzmaAloneCodec;
+
+impl BlockCodec for LzmaAloneCodec {
+    fn decompress(
+        &self,
+        uncompressed_size: u64,
+        compressed_data: Vec<u8>,
+    ) -> std::io::Result<Vec<u8>> {
+        let mut uncompressed_data = Vec::with_capacity(uncompressed_size as usize);
+        lzma_rs::lzma_decompress(&mut compressed_data.as_slice(), &mut uncompressed_data)
+            .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+        Ok(uncompressed_data)
+    }
+}
+
+impl UnityWebFile {
+    pub fn is_compressed(&self) -> bool {
+        self.signature.to_string() == "UnityWeb"
+    }
+
+    /// Codecs to read the data of the bundle, lzma data of `UnityWeb` bundle is read as lzma-alone stream.
+    pub(super) fn get_block_codecs(&self, block_codecs: &Arc<BlockCodecs>) -> Arc<BlockCodecs> {
+        if !self.is_compressed() {
+            return block_codecs.clone();
+        }
+        let mut block_codecs = block_codecs.as_ref().clone();
+        block_codecs.register(CompressionType::Lzma as u8, Arc::new(LzmaAloneCodec));
+        Arc::new(block_codecs)
+    }
+
+    /// Read the directory of the bundle and describe it as a UnityFS file with one storage block.
+    /// The whole data of `UnityWeb` bundle is a single lzma stream,
+    /// so it has to be uncompressed once to get the directory.
+    /// `block_codecs` should be the codecs returned by [`UnityWebFile::get_block_codecs`].
+    pub(super) fn into_unity_fs_file<R: Read + Seek>(
+        self,
+        reader: &mut R,
+        block_codecs: &BlockCodecs,
+    ) -> BinResult<UnityFSFile> {
+        let level = self.levels.last().cloned().ok_or(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "bundle has no level",
+        ))?;
+        let compression_type = if self.is_compressed() {
+            CompressionType::Lzma
+        } else {
+            CompressionType::None
+        };
+
+        reader.seek(SeekFrom::Start(self.header_size as u64))?;
+        let mut blocks_compressedd_stream = vec![0u8; level.compressed_size as usize];
+        reader.read_exact(&mut blocks_compressedd_stream)?;
+        let blocks_uncompressedd_stream = block_codecs.block_uncompressed(
+            level.uncompressed_size as u64,
+            compression_type as u8,
+            blocks_compressedd_stream,
+        )?;
+        let directory_info = DirectoryInfo::read(&mut Cursor::new(blocks_uncompressedd_stream))?;
+
+        let storage_blocks = vec![StorageBlock {
+            uncompressed_size: level.uncompressed_size,
+            compressed_size: level.compressed_size as i32,
+            flags: StorageBlockFlags::new().with_compression_id(compression_type as u8),
+        }];
+        let directory_info: Vec<Node> = directory_info
+            .nodes
+            .into_iter()
+            .map(|node| Node {
+                offset: node.offset as i64,
+                size: node.size as i64,
+                flags: 0,
+                path: node.path,
+            })
+            .collect();
+
+        Ok(UnityFSFile {
+            version: self.version,
+            unity_version: self.unity_version,
+            unity_revision: self.unity_revision,
+            size: self
+                .complete_file_size
+                .unwrap_or(self.header_size + level.compressed_size) as i64,
+            compressed_blocks_info_size: 0,
+            uncompressed_blocks_info_size: 0,
+            flags: ArchiveFlags::new(),
+            blocks_info: BlocksInfo {
+                uncompressed_data_hash: self.hash.unwrap_or_default(),
+                blocks_info_count: storage_blocks.len() as u32,
+                storage_blocks,
+                nodes_count: directory_info.len() as u32,
+                directory_info,
+            },
+            position: self.header_size as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::UnityFS;
+    use super::*;
+    use binrw::BinWrite;
+
+    fn build_legacy_bundle(signature: &str, nodes: &[(&str, &[u8])]) -> Vec<u8> {
+        let directory_size: usize = 4 + nodes.iter().map(|(p, _)| p.len() + 1 + 8).sum::<usize>();
+        let mut directory_info = DirectoryInfo {
+            nodes_count: nodes.len() as i32,
+            nodes: Vec::new(),
+        };
+        let mut data = Vec::new();
+        for (path, node_data) in nodes {
+            directory_info.nodes.push(LegacyNode {
+                path: NullString::from(*path),
+                offset: (directory_size + data.len()) as u32,
+                size: node_data.len() as u32,
+            });
+            data.extend_from_slice(node_data);
+        }
+        let mut blocks = Cursor::new(Vec::new());
+        directory_info.write(&mut blocks).unwrap();
+        blocks.write_all(&data).unwrap();
+        let blocks = blocks.into_inner();
+        let uncompressed_size = blocks.len() as u32;
+        // lzma-alone stream with the uncompressed size, as unity write it.
+        let blocks = if signature == "UnityWeb" {
+            let mut compressed = Vec::new();
+            lzma_rs::lzma_compress_with_options(
+                &mut blocks.as_slice(),
+                &mut compressed,
+                &lzma_rs::compress::Options {
+                    unpacked_size: lzma_rs::compress::UnpackedSize::WriteToHeader(Some(
+                        blocks.len() as u64,
+                    )),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                u64::from_le_bytes(compressed[5..13].try_into().unwrap()),
+                blocks.len() as u64
+            );
+            compressed
+        } else {
+            blocks
+        };
+
+        let mut header = UnityWebFile {
+            signature: NullString::from(signature),
+            version: 3,
+            unity_version: NullString::from("3.x.x"),
+            unity_revision: NullString::from("4.7.2f1"),
+            hash: None,
+            crc: None,
+            minimum_streamed_bytes: blocks.len() as u32,
+            header_size: 0,
+            number_of_levels_to_download_before_streaming: 1,
+            level_count: 1,
+            levels: vec![LevelInfo {
+                compressed_size: blocks.len() as u32,
+                uncompressed_size,
+            }],
+            complete_file_size: Some(0),
+            file_info_header_size: Some(directory_size as u32),
+        };
+        let mut header_writer = Cursor::new(Vec::new());
+        header.write(&mut header_writer).unwrap();
+        header.header_size = header_writer.get_ref().len() as u32;
+        header.complete_file_size = Some(header.header_size + blocks.len() as u32);
+
+        let mut bundle = Cursor::new(Vec::new());
+        header.write(&mut bundle).unwrap();
+        bundle.write_all(&blocks).unwrap();
+        bundle.into_inner()
+    }
+
+    #[test]
+    fn test_legacy_bundle() {
+        let cab: Vec<u8> = (0..5000u32).map(|i| (i % 17) as u8).collect();
+        let ress: Vec<u8> = (0..700u32).map(|i| (i % 5) as u8).collect();
+        for signature in ["UnityWeb", "UnityRaw"] {
+            let bundle = build_legacy_bundle(
+                signature,
+                &[
+                    ("CAB-00000000000000000000000000000000", &cab),
+                    ("CAB-00000000000000000000000000000000.resS", &ress),
+                ],
+            );
+            let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle)), None).unwrap();
+            assert_eq!(unity_fs.get_signature(), signature);
+            assert_eq!(
+                unity_fs.get_file_paths(),
+                vec![
+                    "CAB-00000000000000000000000000000000".to_owned(),
+                    "CAB-00000000000000000000000000000000.resS".to_owned()
+                ]
+            );
+            let mut cab_reader = unity_fs
+                .get_file_reader_by_path(&"CAB-00000000000000000000000000000000".to_owned())
+                .unwrap();
+            let mut cab_buff = vec![0u8; cab.len()];
+            cab_reader.read_exact(&mut cab_buff).unwrap();
+            assert_eq!(cab_buff, cab);
+            assert_eq!(
+                unity_fs
+                    .get_file_data_by_path(&"CAB-00000000000000000000000000000000.resS".to_owned())
+                    .unwrap(),
+                ress
+            );
+        }
+    }
+
+    #[test]
+    fn test_legacy_bundle_node_kinds() {
+        let cab = crate::serialized_file::test::build_serialized_file_header(100, 40);
+        // a header which does not match the node size is not a serialized file.
+        let mut ress = cab.clone();
+        ress.push(0);
+        let bundle = build_legacy_bundle("UnityRaw", &[("CAB-0", &cab), ("CAB-0.resS", &ress)]);
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle)), None).unwrap();
+        let nodes = unity_fs.get_nodes();
+        assert!(nodes[0].is_serialized_file);
+        assert!(!nodes[1].is_serialized_file);
+
+        // node data is not read again, kinds are kept from the read of archive.
+        *unity_fs.file_reader.lock().unwrap() = Box::new(Cursor::new(Vec::new()));
+        unity_fs.set_block_cache_capacity(0);
+        assert_eq!(unity_fs.get_nodes(), nodes);
+        assert_eq!(unity_fs.get_cab_path(), vec!["CAB-0".to_owned()]);
+    }
+}
@@ -0,0 +1,1213 @@
+pub mod block_cache;
+pub mod codec;
+pub mod decryptor;
+pub mod legacy;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod sequential;
+pub mod verify;
+pub mod writer;
+
+use crate::serialized_file::SerializedFile;
+use crate::until::binrw_parser::position_parser;
+use binrw::{binrw, BinResult, Endian, NullString};
+use binrw::{io::Cursor, BinRead};
+use block_cache::BlockCache;
+use codec::BlockCodecs;
+use decryptor::Decryptor;
+use legacy::UnityWebFile;
+use modular_bitfield::specifiers::{B22, B6, B9};
+use modular_bitfield::{bitfield, BitfieldSpecifier};
+use num_enum::TryFromPrimitive;
+use std::fs::File;
+use std::io::{prelude::*, BufWriter, ErrorKind, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub trait UnityResource: std::io::Read + std::io::Seek {}
+
+impl<T: std::io::Read + std::io::Seek> UnityResource for T {}
+
+/// A window of `size` bytes starting at `offset` of a shared resource.
+#[derive(Clone)]
+pub struct ResourceWindow {
+    file_reader: Arc<Mutex<Box<dyn UnityResource + Send>>>,
+    offset: u64,
+    size: u64,
+    current_position: u64,
+}
+
+impl ResourceWindow {
+    pub fn new(
+        file_reader: Arc<Mutex<Box<dyn UnityResource + Send>>>,
+        offset: u64,
+        size: u64,
+    ) -> Self {
+        Self {
+            file_reader,
+            offset,
+            size,
+            current_position: 0,
+        }
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for ResourceWindow {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.current_position >= self.size {
+            return Ok(0);
+        }
+        let read_size = (buf.len() as u64).min(self.size - self.current_position) as usize;
+        let read_size = if let Ok(mut file_reader) = self.file_reader.lock() {
+            file_reader.seek(SeekFrom::Start(self.offset + self.current_position))?;
+            file_reader.read(&mut buf[..read_size])?
+        } else {
+            return Err(std::io::Error::from(ErrorKind::BrokenPipe));
+        };
+        self.current_position += read_size as u64;
+        Ok(read_size)
+    }
+}
+
+impl Seek for ResourceWindow {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::End(offset) => self.size as i64 + offset,
+            SeekFrom::Current(offset) => self.current_position as i64 + offset,
+        };
+        if new_pos < 0 {
+            return Err(std::io::Error::from(ErrorKind::InvalidInput));
+        }
+        self.current_position = new_pos as u64;
+        Ok(self.current_position)
+    }
+}
+
+#[bitfield]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[binrw]
+#[br(map = |x:u32| Self::from_bytes(x.to_le_bytes()))]
+#[bw(map = |&x| <u32>::from_le_bytes(Self::into_bytes(x)))]
+pub struct ArchiveFlags {
+    /// Compression id of the blocks info, which may not be a known [`CompressionType`].
+    pub compression_id: B6,
+    #[allow(dead_code)]
+    blocks_and_directory_info_combined: bool,
+    blocks_info_at_the_end: bool,
+    #[allow(dead_code)]
+    old_web_plugin_compatibility: bool,
+    block_info_need_padding_at_start: bool,
+    #[skip]
+    __: B22,
+}
+
+#[bitfield]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[binrw]
+#[br(map = |x:u16| Self::from_bytes(x.to_le_bytes()))]
+#[bw(map = |&x| <u16>::from_le_bytes(Self::into_bytes(x)))]
+pub struct StorageBlockFlags {
+    /// Compression id of the block, which may not be a known [`CompressionType`].
+    pub compression_id: B6,
+    streamed: bool,
+    #[skip]
+    __: B9,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, TryFromPrimitive, BitfieldSpecifier)]
+#[repr(u32)]
+#[bits = 6]
+pub enum CompressionType {
+    None = 0,
+    Lzma,
+    Lz4,
+    Lz4HC,
+    Lzham,
+}
+
+#[derive(Clone)]
+pub struct UnityFS {
+    signature: String,
+    content: UnityFSFile,
+    file_reader: Arc<Mutex<Box<dyn UnityResource + Send>>>,
+    pub resource_search_path: Option<String>,
+    storage_blocks_start_positions: Vec<(u64, u64)>,
+    decryptor: Option<Arc<dyn Decryptor>>,
+    block_codecs: Arc<BlockCodecs>,
+    block_cache: Arc<Mutex<BlockCache>>,
+    nodes: Vec<NodeInfo>,
+}
+
+#[binrw]
+#[brw(big)]
+#[brw(magic = b"UnityFS\0")]
+#[br(import(block_codecs: Arc<BlockCodecs>))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnityFSFile {
+    version: u32,
+    unity_version: NullString,
+    unity_revision: NullString,
+    size: i64,
+    compressed_blocks_info_size: u32,
+    uncompressed_blocks_info_size: u32,
+    flags: ArchiveFlags,
+    #[br(parse_with = blocks_info_parser, args (version, compressed_blocks_info_size,uncompressed_blocks_info_size,flags,block_codecs))]
+    #[bw(ignore)]
+    blocks_info: BlocksInfo,
+    #[br(parse_with = position_parser)]
+    #[bw(ignore)]
+    position: u64,
+}
+
+impl UnityFS {
+    pub fn get_file_data_by_path(&self, path: &String) -> std::io::Result<Vec<u8>> {
+        for node in &self.content.blocks_info.directory_info {
+            if path == &node.path() {
+                return self.get_file_by_node(node);
+            }
+        }
+        Err(std::io::Error::from(ErrorKind::NotFound))
+    }
+
+    pub fn get_file_reader_by_path(&self, path: &String) -> Option<UnityFSNode> {
+        for node in &self.content.blocks_info.directory_info {
+            if path == &node.path() {
+                return Some(UnityFSNode {
+                    file_reader: self.file_reader.clone(),
+                    storage_blocks: self.content.blocks_info.storage_blocks.clone(),
+                    storage_blocks_start_positions: self.storage_blocks_start_positions.clone(),
+                    storage_block_position: self.content.position,
+                    decryptor: self.decryptor.clone(),
+                    block_codecs: self.block_codecs.clone(),
+                    block_cache: self.block_cache.clone(),
+                    node_info: node.clone(),
+                    current_position: 0,
+                });
+            }
+        }
+        None
+    }
+
+    fn get_file_by_node(&self, node: &Node) -> std::io::Result<Vec<u8>> {
+        let mut compressed_data_offset = 0u64;
+        let mut uncompressed_data_offset = 0u64;
+        let mut file_block = Vec::new();
+        for (block_index, sb) in self.content.blocks_info.storage_blocks.iter().enumerate() {
+            if (uncompressed_data_offset + (sb.uncompressed_size as u64)) >= node.offset as u64 {
+                let blocks_info_uncompressedd_stream = read_storage_block(
+                    &self.file_reader,
+                    compressed_data_offset + self.content.position,
+                    block_index,
+                    sb,
+                    &self.decryptor,
+                    &self.block_codecs,
+                    &self.block_cache,
+                )?;
+                if uncompressed_data_offset < node.offset as u64 {
+                    file_block.extend_from_slice(
+                        &blocks_info_uncompressedd_stream
+                            [(node.offset as u64 - uncompressed_data_offset) as usize..],
+                    );
+                } else {
+                    file_block.extend_from_slice(&blocks_info_uncompressedd_stream);
+                }
+                if file_block.len() >= node.size as usize {
+                    file_block.truncate(node.size as usize);
+                    return Ok(file_block);
+                }
+            }
+            compressed_data_offset += sb.compressed_size as u64;
+            uncompressed_data_offset += sb.uncompressed_size as u64;
+        }
+        Err(std::io::Error::from(ErrorKind::NotFound))
+    }
+
+    /// Paths of the serialized files in the archive, see [`UnityFS::get_nodes`].
+    pub fn get_cab_path(&self) -> Vec<String> {
+        self.get_nodes()
+            .into_iter()
+            .filter(|node| node.is_serialized_file)
+            .map(|node| node.path)
+            .collect()
+    }
+
+    /// List nodes of the archive. A node of UnityFS archive is a serialized file if it has
+    /// [`NODE_FLAG_SERIALIZED_FILE`]. Nodes of legacy bundles have no flags,
+    /// so the header of their data is checked once when the archive is read.
+    pub fn get_nodes(&self) -> Vec<NodeInfo> {
+        self.nodes.clone()
+    }
+
+    fn read_nodes(&self) -> Vec<NodeInfo> {
+        let is_legacy = self.signature != "UnityFS";
+        self.content
+            .blocks_info
+            .directory_info
+            .iter()
+            .map(|node| {
+                let is_serialized_file = node.flags & NODE_FLAG_SERIALIZED_FILE != 0
+                    || (is_legacy
+                        && node.flags & NODE_FLAG_DIRECTORY == 0
+                        && self.get_file_reader_by_path(&node.path()).is_some_and(
+                            |mut node_reader| SerializedFile::is_serialized_file(&mut node_reader),
+                        ));
+                NodeInfo {
+                    path: node.path(),
+                    offset: node.offset as u64,
+                    size: node.size as u64,
+                    flags: node.flags,
+                    is_serialized_file,
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_header_info(&self) -> UnityFSHeaderInfo {
+        UnityFSHeaderInfo {
+            signature: self.signature.clone(),
+            version: self.content.version,
+            unity_version: self.content.unity_version.to_string(),
+            unity_revision: self.content.unity_revision.to_string(),
+            size: self.content.size as u64,
+            blocks_info_compression_id: self.content.flags.compression_id(),
+            blocks_info_at_the_end: self.content.flags.blocks_info_at_the_end(),
+            storage_blocks: self
+                .content
+                .blocks_info
+                .storage_blocks
+                .iter()
+                .map(|sb| StorageBlockInfo {
+                    compressed_size: sb.compressed_size as u32,
+                    uncompressed_size: sb.uncompressed_size,
+                    compression_id: sb.flags.compression_id(),
+                    streamed: sb.flags.streamed(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get_file_paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+        for file in &self.content.blocks_info.directory_info {
+            paths.push(file.path());
+        }
+        paths
+    }
+
+    /// Write every node to `out_dir` under its path in the archive, return the written file paths.
+    /// Node data is streamed block by block, so nodes are never held in memory as a whole.
+    /// Paths which would escape `out_dir` are rejected with [`ErrorKind::InvalidData`].
+    pub fn extract_all_to_dir<P: AsRef<Path>>(&self, out_dir: P) -> std::io::Result<Vec<PathBuf>> {
+        let mut out_paths = Vec::new();
+        for node in &self.content.blocks_info.directory_info {
+            let path = node.path();
+            let mut out_path = out_dir.as_ref().to_path_buf();
+            for component in Path::new(&path).components() {
+                match component {
+                    Component::Normal(name) => out_path.push(name),
+                    Component::CurDir => (),
+                    _ => {
+                        return Err(std::io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!("node path {:?} is out of the output dir", path),
+                        ))
+                    }
+                }
+            }
+            if node.flags & NODE_FLAG_DIRECTORY != 0 {
+                std::fs::create_dir_all(&out_path)?;
+                continue;
+            }
+            if let Some(parent) = out_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut node_reader = self
+                .get_file_reader_by_path(&path)
+                .ok_or(std::io::Error::from(ErrorKind::NotFound))?;
+            let mut out_file = BufWriter::new(File::create(&out_path)?);
+            std::io::copy(&mut node_reader, &mut out_file)?;
+            out_file.flush()?;
+            out_paths.push(out_path);
+        }
+        Ok(out_paths)
+    }
+
+    /// Check the signature to find out whether the reader contain a UnityFS, UnityWeb or UnityRaw archive.
+    /// The reader position is restored after check.
+    pub fn is_unity_fs<R: Read + Seek>(reader: &mut R) -> bool {
+        if let Ok(pos) = reader.stream_position() {
+            let mut magic = [0u8; 8];
+            let is_unity_fs = reader.read_exact(&mut magic).is_ok()
+                && matches!(&magic, b"UnityFS\0" | b"UnityWeb" | b"UnityRaw");
+            if reader.seek(SeekFrom::Start(pos)).is_ok() {
+                return is_unity_fs;
+            }
+        }
+        false
+    }
+
+    /// Budget in bytes of the uncompressed block cache shared by this archive and its nodes.
+    pub fn get_block_cache_capacity(&self) -> usize {
+        self.block_cache.lock().unwrap().get_capacity()
+    }
+
+    /// Set `0` to disable caching.
+    pub fn set_block_cache_capacity(&self, capacity: usize) {
+        self.block_cache.lock().unwrap().set_capacity(capacity)
+    }
+
+    pub fn get_signature(&self) -> &String {
+        &self.signature
+    }
+
+    /// Read UnityFS archive, the legacy `UnityWeb` and `UnityRaw` bundles are also accepted.
+    pub fn read(
+        file: Box<dyn UnityResource + Send>,
+        resource_search_path: Option<String>,
+    ) -> BinResult<UnityFS> {
+        Self::read_with_decryptor(file, resource_search_path, None)
+    }
+
+    /// Read obfuscated archive. The resource is decrypted by [`Decryptor::decrypt_resource`]
+    /// before read and every storage block by [`Decryptor::decrypt_block`] before uncompressed.
+    pub fn read_with_decryptor(
+        file: Box<dyn UnityResource + Send>,
+        resource_search_path: Option<String>,
+        decryptor: Option<Arc<dyn Decryptor>>,
+    ) -> BinResult<UnityFS> {
+        Self::read_with_block_codecs(
+            file,
+            resource_search_path,
+            decryptor,
+            BlockCodecs::get_default(),
+        )
+    }
+
+    /// Read archive whose blocks are uncompressed by `block_codecs` instead of the built-in codecs,
+    /// see [`BlockCodecs::register`].
+    pub fn read_with_block_codecs(
+        file: Box<dyn UnityResource + Send>,
+        resource_search_path: Option<String>,
+        decryptor: Option<Arc<dyn Decryptor>>,
+        block_codecs: Arc<BlockCodecs>,
+    ) -> BinResult<UnityFS> {
+        let file = match &decryptor {
+            Some(decryptor) => decryptor.decrypt_resource(file)?,
+            None => file,
+        };
+        Self::read_decrypted(file, resource_search_path, decryptor, block_codecs)
+    }
+
+    /// Read archive from a resource which is already decrypted by [`Decryptor::decrypt_resource`].
+    pub(crate) fn read_decrypted(
+        mut file: Box<dyn UnityResource + Send>,
+        resource_search_path: Option<String>,
+        decryptor: Option<Arc<dyn Decryptor>>,
+        mut block_codecs: Arc<BlockCodecs>,
+    ) -> BinResult<UnityFS> {
+        let pos = file.stream_position()?;
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        file.seek(SeekFrom::Start(pos))?;
+        let (signature, content) = match &magic {
+            b"UnityFS\0" => (
+                "UnityFS",
+                UnityFSFile::read_args(&mut file, (block_codecs.clone(),))?,
+            ),
+            b"UnityWeb" | b"UnityRaw" => {
+                let legacy_file = UnityWebFile::read(&mut file)?;
+                let signature = if legacy_file.is_compressed() {
+                    "UnityWeb"
+                } else {
+                    "UnityRaw"
+                };
+                block_codecs = legacy_file.get_block_codecs(&block_codecs);
+                (
+                    signature,
+                    legacy_file.into_unity_fs_file(&mut file, &block_codecs)?,
+                )
+            }
+            _ => {
+                return Err(binrw::Error::BadMagic {
+                    pos,
+                    found: Box::new(magic),
+                })
+            }
+        };
+        let storage_blocks_start_positions = {
+            let mut compressed_data_offset = 0;
+            let mut uncompressed_data_offset = 0;
+            let mut storage_blocks_positions = Vec::new();
+            for storage_block in &content.blocks_info.storage_blocks {
+                storage_blocks_positions.push((compressed_data_offset, uncompressed_data_offset));
+                compressed_data_offset += storage_block.compressed_size as u64;
+                uncompressed_data_offset += storage_block.uncompressed_size as u64;
+            }
+            storage_blocks_positions
+        };
+        let mut unityfs = UnityFS {
+            signature: signature.to_owned(),
+            content,
+            file_reader: Arc::new(Mutex::new(file)),
+            resource_search_path,
+            storage_blocks_start_positions,
+            decryptor,
+            block_codecs,
+            block_cache: Arc::new(Mutex::new(BlockCache::default())),
+            nodes: Vec::new(),
+        };
+        unityfs.nodes = unityfs.read_nodes();
+        // blocks read to check node headers of legacy bundles are not kept.
+        unityfs.block_cache.lock().unwrap().clear();
+        Ok(unityfs)
+    }
+
+    /// Find every UnityFS archive in the resource, include archives after leading junk bytes
+    /// and archives concatenated into one file.
+    /// Each archive is exposed as a window of the resource which can be passed to [`UnityFS::read`].
+    /// Legacy `UnityWeb` and `UnityRaw` bundles are returned as a single window of the whole file.
+    pub fn scan_archives(
+        file: Box<dyn UnityResource + Send>,
+    ) -> std::io::Result<Vec<ResourceWindow>> {
+        Self::scan_archives_with_block_codecs(file, &BlockCodecs::get_default())
+    }
+
+    /// Same as [`UnityFS::scan_archives`], archives are checked with `block_codecs`
+    /// so archives using registered codecs are also found.
+    pub fn scan_archives_with_block_codecs(
+        mut file: Box<dyn UnityResource + Send>,
+        block_codecs: &Arc<BlockCodecs>,
+    ) -> std::io::Result<Vec<ResourceWindow>> {
+        let file_size = file.seek(SeekFrom::End(0))?;
+        let mut magic = [0u8; 8];
+        file.seek(SeekFrom::Start(0))?;
+        let is_legacy =
+            file.read_exact(&mut magic).is_ok() && matches!(&magic, b"UnityWeb" | b"UnityRaw");
+        let file_reader = Arc::new(Mutex::new(file));
+        if is_legacy {
+            return Ok(vec![ResourceWindow::new(file_reader, 0, file_size)]);
+        }
+
+        let mut archives = Vec::new();
+        let mut search_position = 0;
+        loop {
+            let mut file = file_reader.lock().unwrap();
+            let Some(offset) = find_signature(&mut *file, search_position, b"UnityFS\0")? else {
+                break;
+            };
+            drop(file);
+
+            // the blocks info may be at the end of the archive,
+            // so read the archive size first and check the archive inside its own window.
+            let mut header_reader =
+                ResourceWindow::new(file_reader.clone(), offset + 8, file_size - offset - 8);
+            let archive_size = (|| -> BinResult<i64> {
+                let _version = u32::read_be(&mut header_reader)?;
+                let _unity_version = NullString::read(&mut header_reader)?;
+                let _unity_revision = NullString::read(&mut header_reader)?;
+                i64::read_be(&mut header_reader)
+            })()
+            .unwrap_or_default();
+            if archive_size > 0 && offset + archive_size as u64 <= file_size {
+                let mut archive_reader =
+                    ResourceWindow::new(file_reader.clone(), offset, archive_size as u64);
+                if UnityFSFile::read_args(&mut archive_reader, (block_codecs.clone(),)).is_ok() {
+                    archive_reader.current_position = 0;
+                    archives.push(archive_reader);
+                    search_position = offset + archive_size as u64;
+                    continue;
+                }
+            }
+            search_position = offset + 1;
+        }
+        Ok(archives)
+    }
+}
+
+fn find_signature<R: Read + Seek + ?Sized>(
+    reader: &mut R,
+    start_position: u64,
+    signature: &[u8],
+) -> std::io::Result<Option<u64>> {
+    let mut buff_position = reader.seek(SeekFrom::Start(start_position))?;
+    let mut buff = vec![0u8; 0x10000];
+    let mut buff_len = 0;
+    loop {
+        let read_size = reader.read(&mut buff[buff_len..])?;
+        buff_len += read_size;
+        if let Some(index) = buff[..buff_len]
+            .windows(signature.len())
+            .position(|window| window == signature)
+        {
+            return Ok(Some(buff_position + index as u64));
+        }
+        if read_size == 0 {
+            return Ok(None);
+        }
+        // keep the tail which may be the start of a signature.
+        let keep = buff_len.min(signature.len() - 1);
+        buff.copy_within(buff_len - keep..buff_len, 0);
+        buff_position += (buff_len - keep) as u64;
+        buff_len = keep;
+    }
+}
+
+#[binrw]
+#[brw(big)]
+#[derive(Clone, Debug, PartialEq)]
+struct BlocksInfo {
+    uncompressed_data_hash: [u8; 16],
+    blocks_info_count: u32,
+    #[br(count = blocks_info_count)]
+    storage_blocks: Vec<StorageBlock>,
+    nodes_count: u32,
+    #[br(count = nodes_count)]
+    directory_info: Vec<Node>,
+}
+
+#[binrw]
+#[brw(big)]
+#[derive(Clone, Debug, PartialEq)]
+struct StorageBlock {
+    uncompressed_size: u32,
+    compressed_size: i32,
+    flags: StorageBlockFlags,
+}
+
+#[binrw]
+#[brw(big)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    offset: i64,
+    size: i64,
+    flags: u32,
+    path: NullString,
+}
+
+pub const NODE_FLAG_DIRECTORY: u32 = 1;
+pub const NODE_FLAG_DELETED: u32 = 2;
+pub const NODE_FLAG_SERIALIZED_FILE: u32 = 4;
+
+/// Node in the directory of archive, returned by [`UnityFS::get_nodes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub path: String,
+    /// Offset in the uncompressed data of archive.
+    pub offset: u64,
+    pub size: u64,
+    pub flags: u32,
+    pub is_serialized_file: bool,
+}
+
+/// Header of archive, returned by [`UnityFS::get_header_info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnityFSHeaderInfo {
+    /// `UnityFS`, or `UnityWeb` and `UnityRaw` for legacy bundles.
+    pub signature: String,
+    pub version: u32,
+    pub unity_version: String,
+    pub unity_revision: String,
+    pub size: u64,
+    /// See [`codec::BlockCodecs`].
+    pub blocks_info_compression_id: u8,
+    pub blocks_info_at_the_end: bool,
+    pub storage_blocks: Vec<StorageBlockInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageBlockInfo {
+    pub compressed_size: u32,
+    pub uncompressed_size: u32,
+    pub compression_id: u8,
+    pub streamed: bool,
+}
+
+impl Node {
+    pub fn path(&self) -> String {
+        self.path.clone().to_string()
+    }
+}
+
+fn blocks_info_parser<R: Read + Seek>(
+    reader: &mut R,
+    _endian: Endian,
+    flags: (u32, u32, u32, ArchiveFlags, Arc<BlockCodecs>),
+) -> BinResult<BlocksInfo> {
+    let (version, compressed_blocks_info_size, uncompressed_blocks_info_size, flags, block_codecs) =
+        flags;
+
+    if version >= 7 {
+        let pos = reader.stream_position()?;
+        if pos % 16 != 0 {
+            reader.seek(SeekFrom::Current((16 - (pos % 16)) as i64))?;
+        }
+    }
+
+    let mut blocks_infocompressedd_stream = vec![0u8; compressed_blocks_info_size as usize];
+
+    if flags.blocks_info_at_the_end() {
+        let pos = reader.stream_position()?;
+        reader.seek(SeekFrom::End(-(compressed_blocks_info_size as i64)))?;
+        reader.read_exact(&mut blocks_infocompressedd_stream)?;
+        reader.seek(SeekFrom::Start(pos))?;
+    } else {
+        reader.read_exact(&mut blocks_infocompressedd_stream)?;
+    }
+
+    if flags.block_info_need_padding_at_start() {
+        let pos = reader.stream_position()?;
+        if pos % 16 != 0 {
+            reader.seek(SeekFrom::Current((16 - (pos % 16)) as i64))?;
+        }
+    }
+
+    let blocks_info_uncompressedd_stream = block_codecs.block_uncompressed(
+        uncompressed_blocks_info_size as u64,
+        flags.compression_id(),
+        blocks_infocompressedd_stream,
+    )?;
+
+    let mut blocks_info_reader = Cursor::new(blocks_info_uncompressedd_stream);
+    BlocksInfo::read(&mut blocks_info_reader)
+}
+
+/// Read the storage block at `block_index` and uncompress it,
+/// the uncompressed block is looked up in and saved to `block_cache`.
+fn read_storage_block(
+    file_reader: &Mutex<Box<dyn UnityResource + Send>>,
+    compressed_data_position: u64,
+    block_index: usize,
+    storage_block: &StorageBlock,
+    decryptor: &Option<Arc<dyn Decryptor>>,
+    block_codecs: &BlockCodecs,
+    block_cache: &Mutex<BlockCache>,
+) -> std::io::Result<Arc<Vec<u8>>> {
+    if let Some(cache_block) = block_cache.lock().unwrap().get(block_index) {
+        return Ok(cache_block);
+    }
+    let mut blocks_compressedd_stream = vec![0u8; storage_block.compressed_size as usize];
+    if let Ok(mut file_reader) = file_reader.lock() {
+        file_reader.seek(SeekFrom::Start(compressed_data_position))?;
+        file_reader.read_exact(&mut blocks_compressedd_stream)?;
+    } else {
+        return Err(std::io::Error::from(ErrorKind::BrokenPipe));
+    }
+    if let Some(decryptor) = decryptor {
+        blocks_compressedd_stream = decryptor.decrypt_block(
+            block_index,
+            storage_block.flags.compression_id(),
+            blocks_compressedd_stream,
+        )?;
+    }
+    let blocks_uncompressedd_stream = Arc::new(block_codecs.block_uncompressed(
+        storage_block.uncompressed_size as u64,
+        storage_block.flags.compression_id(),
+        blocks_compressedd_stream,
+    )?);
+    block_cache
+        .lock()
+        .unwrap()
+        .insert(block_index, blocks_uncompressedd_stream.clone());
+    Ok(blocks_uncompressedd_stream)
+}
+
+#[derive(Clone)]
+pub struct UnityFSNode {
+    file_reader: Arc<Mutex<Box<dyn UnityResource + Send>>>,
+    storage_blocks: Vec<StorageBlock>,
+    storage_blocks_start_positions: Vec<(u64, u64)>,
+    storage_block_position: u64,
+    node_info: Node,
+    current_position: u64,
+    decryptor: Option<Arc<dyn Decryptor>>,
+    block_codecs: Arc<BlockCodecs>,
+    block_cache: Arc<Mutex<BlockCache>>,
+}
+
+impl Read for UnityFSNode {
+    /// Read from the blocks which contain the node data, never read past the end of the node.
+    /// Return `Ok(0)` at the end of the node.
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let node_size = self.node_info.size as u64;
+        if buf.is_empty() || self.current_position >= node_size {
+            return Ok(0);
+        }
+        let read_size = (buf.len() as u64).min(node_size - self.current_position) as usize;
+        let mut uncompressed_data_read_offset =
+            (self.node_info.offset as u64) + self.current_position;
+        let mut storage_blocks_index =
+            match self.storage_blocks_start_positions.binary_search_by_key(
+                &uncompressed_data_read_offset,
+                |&(_compressed_data_offset, uncompressed_data_offset)| uncompressed_data_offset,
+            ) {
+                Ok(index) => index,
+                Err(rindex) => rindex.saturating_sub(1),
+            };
+
+        let mut readed_size = 0;
+        while readed_size < read_size {
+            let Some(sb) = self.storage_blocks.get(storage_blocks_index) else {
+                break;
+            };
+            let (compressed_data_offset, uncompressed_data_offset) =
+                self.storage_blocks_start_positions[storage_blocks_index];
+            let blocks_uncompressedd_stream = read_storage_block(
+                &self.file_reader,
+                compressed_data_offset + self.storage_block_position,
+                storage_blocks_index,
+                sb,
+                &self.decryptor,
+                &self.block_codecs,
+                &self.block_cache,
+            )?;
+            let block_offset = ((uncompressed_data_read_offset - uncompressed_data_offset)
+                as usize)
+                .min(blocks_uncompressedd_stream.len());
+            let copy_size =
+                (blocks_uncompressedd_stream.len() - block_offset).min(read_size - readed_size);
+            buf[readed_size..readed_size + copy_size].copy_from_slice(
+                &blocks_uncompressedd_stream[block_offset..block_offset + copy_size],
+            );
+            readed_size += copy_size;
+            uncompressed_data_read_offset += copy_size as u64;
+            storage_blocks_index += 1;
+        }
+        if readed_size == 0 {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "node data is out of storage blocks",
+            ));
+        }
+        self.current_position += readed_size as u64;
+        Ok(readed_size)
+    }
+}
+
+impl Seek for UnityFSNode {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match pos {
+            SeekFrom::Start(pos) => self.current_position = pos,
+            SeekFrom::End(offset) => {
+                let new_pos = self.node_info.size + offset;
+                if new_pos >= 0 {
+                    self.current_position = new_pos as u64;
+                } else {
+                    self.current_position = 0;
+                }
+            }
+            SeekFrom::Current(offset) => {
+                let new_pos = self.current_position as i64 + offset;
+                if new_pos >= 0 {
+                    self.current_position = new_pos as u64;
+                } else {
+                    self.current_position = 0;
+                }
+            }
+        }
+        Ok(self.current_position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lzma_block_uncompressed() {
+        let data: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress_with_options(
+            &mut data.as_slice(),
+            &mut compressed,
+            &lzma_rs::compress::Options {
+                unpacked_size: lzma_rs::compress::UnpackedSize::SkipWritingToHeader,
+            },
+        )
+        .unwrap();
+
+        let block_codecs = BlockCodecs::get_default();
+        let uncompressed = block_codecs
+            .block_uncompressed(data.len() as u64, CompressionType::Lzma as u8, compressed)
+            .unwrap();
+        assert_eq!(uncompressed, data);
+
+        let err = block_codecs
+            .block_uncompressed(0, CompressionType::Lzham as u8, vec![])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_unity_fs_builder() {
+        let cab: Vec<u8> = (0..10000u32).map(|i| (i % 13) as u8).collect();
+        let ress: Vec<u8> = (0..3000u32).map(|i| (i % 7) as u8).collect();
+        for (compression_type, version, at_the_end, padding) in [
+            (CompressionType::None, 6, false, false),
+            (CompressionType::Lz4, 7, true, false),
+            (CompressionType::Lz4HC, 8, false, true),
+            (CompressionType::Lzma, 7, true, true),
+        ] {
+            let mut bundle = Cursor::new(Vec::new());
+            writer::UnityFSBuilder::new()
+                .version(version)
+                .block_compression(compression_type)
+                .blocks_info_compression(compression_type)
+                .block_size(4096)
+                .blocks_info_at_the_end(at_the_end)
+                .block_info_need_padding_at_start(padding)
+                .add_node(
+                    "CAB-00000000000000000000000000000000",
+                    4,
+                    Box::new(Cursor::new(cab.clone())),
+                )
+                .add_node(
+                    "CAB-00000000000000000000000000000000.resS",
+                    0,
+                    Box::new(Cursor::new(ress.clone())),
+                )
+                .write(&mut bundle)
+                .unwrap();
+            let bundle = bundle.into_inner();
+
+            let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.clone())), None).unwrap();
+            assert_eq!(unity_fs.content.size as usize, bundle.len());
+            assert_eq!(unity_fs.content.blocks_info.storage_blocks.len(), 4);
+            assert_eq!(
+                unity_fs.get_cab_path(),
+                vec!["CAB-00000000000000000000000000000000".to_owned()]
+            );
+            let nodes = unity_fs.get_nodes();
+            assert_eq!(nodes.len(), 2);
+            assert_eq!((nodes[0].offset, nodes[0].size), (0, cab.len() as u64));
+            assert_eq!(nodes[0].flags, NODE_FLAG_SERIALIZED_FILE);
+            assert!(nodes[0].is_serialized_file);
+            assert_eq!(nodes[1].offset, cab.len() as u64);
+            assert!(!nodes[1].is_serialized_file);
+            let header_info = unity_fs.get_header_info();
+            assert_eq!(header_info.version, version);
+            assert_eq!(header_info.blocks_info_at_the_end, at_the_end);
+            assert_eq!(
+                header_info.blocks_info_compression_id,
+                compression_type as u8
+            );
+            assert!(header_info
+                .storage_blocks
+                .iter()
+                .all(|sb| sb.compression_id == compression_type as u8));
+            assert_eq!(
+                unity_fs
+                    .get_file_data_by_path(&"CAB-00000000000000000000000000000000".to_owned())
+                    .unwrap(),
+                cab
+            );
+            assert_eq!(
+                unity_fs
+                    .get_file_data_by_path(&"CAB-00000000000000000000000000000000.resS".to_owned())
+                    .unwrap(),
+                ress
+            );
+        }
+    }
+
+    #[test]
+    fn test_unity_fs_node_kinds_from_flags() {
+        let cab = crate::serialized_file::test::build_serialized_file_header(100, 40);
+        let mut bundle = Cursor::new(Vec::new());
+        writer::UnityFSBuilder::new()
+            .add_node(
+                "CAB-0",
+                NODE_FLAG_SERIALIZED_FILE,
+                Box::new(Cursor::new(cab.clone())),
+            )
+            .add_node("CAB-1", 0, Box::new(Cursor::new(cab)))
+            .write(&mut bundle)
+            .unwrap();
+
+        // node data is not read to find serialized files of UnityFS archive.
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.into_inner())), None).unwrap();
+        assert_eq!(unity_fs.block_cache.lock().unwrap().get_size(), 0);
+        let nodes = unity_fs.get_nodes();
+        assert!(nodes[0].is_serialized_file);
+        assert!(!nodes[1].is_serialized_file);
+        assert_eq!(unity_fs.get_cab_path(), vec!["CAB-0".to_owned()]);
+    }
+
+    #[test]
+    fn test_scan_archives() {
+        let build_bundle = |version: u32, at_the_end: bool, node_path: &str, data: &[u8]| {
+            let mut bundle = Cursor::new(Vec::new());
+            writer::UnityFSBuilder::new()
+                .version(version)
+                .blocks_info_at_the_end(at_the_end)
+                .add_node(node_path, 4, Box::new(Cursor::new(data.to_vec())))
+                .write(&mut bundle)
+                .unwrap();
+            bundle.into_inner()
+        };
+        let first_cab: Vec<u8> = (0..2000u32).map(|i| (i % 11) as u8).collect();
+        let second_cab: Vec<u8> = (0..3000u32).map(|i| (i % 3) as u8).collect();
+
+        let mut file = b"junk data UnityFS\0 truncated header".to_vec();
+        let first_offset = file.len() as u64;
+        file.extend(build_bundle(6, false, "CAB-first", &first_cab));
+        file.extend(vec![0xffu8; 13]);
+        let second_offset = file.len() as u64;
+        file.extend(build_bundle(7, true, "CAB-second", &second_cab));
+        file.extend(b"trailing junk");
+
+        let archives = UnityFS::scan_archives(Box::new(Cursor::new(file))).unwrap();
+        assert_eq!(
+            archives
+                .iter()
+                .map(|archive| archive.get_offset())
+                .collect::<Vec<_>>(),
+            vec![first_offset, second_offset]
+        );
+        for (archive, (cab_path, cab)) in archives
+            .into_iter()
+            .zip([("CAB-first", &first_cab), ("CAB-second", &second_cab)])
+        {
+            let unity_fs = UnityFS::read(Box::new(archive), None).unwrap();
+            assert_eq!(unity_fs.get_file_paths(), vec![cab_path.to_owned()]);
+            assert_eq!(
+                &unity_fs
+                    .get_file_data_by_path(&cab_path.to_owned())
+                    .unwrap(),
+                cab
+            );
+        }
+    }
+
+    struct XorDecryptor {
+        resource_key: Option<u8>,
+        block_key: Option<u8>,
+    }
+
+    impl Decryptor for XorDecryptor {
+        fn decrypt_resource(
+            &self,
+            mut file: Box<dyn UnityResource + Send>,
+        ) -> std::io::Result<Box<dyn UnityResource + Send>> {
+            let Some(key) = self.resource_key else {
+                return Ok(file);
+            };
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            data.iter_mut().for_each(|b| *b ^= key);
+            Ok(Box::new(Cursor::new(data)))
+        }
+
+        fn decrypt_block(
+            &self,
+            block_index: usize,
+            _compression_id: u8,
+            mut block_data: Vec<u8>,
+        ) -> std::io::Result<Vec<u8>> {
+            if let Some(key) = self.block_key {
+                block_data
+                    .iter_mut()
+                    .for_each(|b| *b ^= key.wrapping_add(block_index as u8));
+            }
+            Ok(block_data)
+        }
+    }
+
+    #[test]
+    fn test_decryptor() {
+        let cab: Vec<u8> = (0..10000u32).map(|i| (i % 13) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        writer::UnityFSBuilder::new()
+            .block_compression(CompressionType::None)
+            .block_size(4096)
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let bundle = bundle.into_inner();
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.clone())), None).unwrap();
+        let data_start = unity_fs.content.position as usize;
+
+        // obfuscate every storage block with its own key.
+        let mut block_obfuscated = bundle.clone();
+        for (block_index, (compressed_data_offset, _)) in
+            unity_fs.storage_blocks_start_positions.iter().enumerate()
+        {
+            let start = data_start + *compressed_data_offset as usize;
+            let end = (start + 4096).min(block_obfuscated.len());
+            block_obfuscated[start..end]
+                .iter_mut()
+                .for_each(|b| *b ^= 0x5a_u8.wrapping_add(block_index as u8));
+        }
+        // obfuscate the whole file, include the header.
+        let resource_obfuscated: Vec<u8> = block_obfuscated.iter().map(|b| b ^ 0xa5).collect();
+
+        let unity_fs = UnityFS::read_with_decryptor(
+            Box::new(Cursor::new(resource_obfuscated)),
+            None,
+            Some(Arc::new(XorDecryptor {
+                resource_key: Some(0xa5),
+                block_key: Some(0x5a),
+            })),
+        )
+        .unwrap();
+        assert_eq!(
+            unity_fs.get_file_data_by_path(&"CAB-0".to_owned()).unwrap(),
+            cab
+        );
+        let mut cab_reader = unity_fs
+            .get_file_reader_by_path(&"CAB-0".to_owned())
+            .unwrap();
+        let mut cab_buff = vec![0u8; cab.len()];
+        cab_reader.read_exact(&mut cab_buff).unwrap();
+        assert_eq!(cab_buff, cab);
+    }
+
+    #[test]
+    fn test_shared_block_cache() {
+        let ress: Vec<u8> = (0..20000u32).map(|i| (i % 251) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        writer::UnityFSBuilder::new()
+            .block_size(4096)
+            .add_node("CAB-0.resS", 0, Box::new(Cursor::new(ress.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.into_inner())), None).unwrap();
+        unity_fs.set_block_cache_capacity(3 * 4096);
+        let path = "CAB-0.resS".to_owned();
+
+        let mut first_reader = unity_fs.get_file_reader_by_path(&path).unwrap();
+        let mut second_reader = first_reader.clone();
+        let mut buff = vec![0u8; 1000];
+        first_reader.seek(SeekFrom::Start(5000)).unwrap();
+        first_reader.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, ress[5000..6000]);
+        // the block read by first reader is shared with second reader.
+        assert_eq!(unity_fs.block_cache.lock().unwrap().get_size(), 4096);
+        second_reader.seek(SeekFrom::Start(4500)).unwrap();
+        second_reader.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, ress[4500..5500]);
+        assert_eq!(unity_fs.block_cache.lock().unwrap().get_size(), 4096);
+
+        assert_eq!(unity_fs.get_file_data_by_path(&path).unwrap(), ress);
+        assert!(unity_fs.block_cache.lock().unwrap().get_size() <= 3 * 4096);
+
+        unity_fs.set_block_cache_capacity(0);
+        assert_eq!(unity_fs.get_file_data_by_path(&path).unwrap(), ress);
+        assert_eq!(unity_fs.block_cache.lock().unwrap().get_size(), 0);
+    }
+
+    #[test]
+    fn test_unity_fs_node_read() {
+        let cab: Vec<u8> = (0..10000u32).map(|i| (i % 13) as u8).collect();
+        let ress: Vec<u8> = (0..3000u32).map(|i| (i % 7) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        writer::UnityFSBuilder::new()
+            .block_size(4096)
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+            .add_node("CAB-0.resS", 0, Box::new(Cursor::new(ress.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.into_inner())), None).unwrap();
+
+        for (path, data) in [("CAB-0", &cab), ("CAB-0.resS", &ress)] {
+            let mut node_reader = unity_fs.get_file_reader_by_path(&path.to_owned()).unwrap();
+            let mut node_data = Vec::new();
+            node_reader.read_to_end(&mut node_data).unwrap();
+            assert_eq!(&node_data, data);
+            assert_eq!(node_reader.read(&mut [0u8; 16]).unwrap(), 0);
+
+            // read across block boundary in small chunks.
+            node_reader.seek(SeekFrom::Start(4090)).unwrap();
+            let mut chunk = [0u8; 7];
+            let read_size = node_reader.read(&mut chunk).unwrap();
+            assert_eq!(
+                chunk[..read_size],
+                data[4090.min(data.len())..][..read_size]
+            );
+        }
+    }
+
+    #[test]
+    fn test_unity_fs_node_read_across_blocks() {
+        let cab: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let ress: Vec<u8> = (0..500u32).map(|i| (i % 241) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        writer::UnityFSBuilder::new()
+            .block_compression(CompressionType::Lz4)
+            .block_size(64)
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+            .add_node("CAB-0.resS", 0, Box::new(Cursor::new(ress.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.into_inner())), None).unwrap();
+        assert_eq!(
+            unity_fs.content.blocks_info.storage_blocks.len(),
+            1500usize.div_ceil(64)
+        );
+
+        for cache_capacity in [0, block_cache::DEFAULT_BLOCK_CACHE_CAPACITY] {
+            unity_fs.set_block_cache_capacity(cache_capacity);
+            // resS starts at 1000, which is in the middle of block 15.
+            for (path, data) in [("CAB-0", &cab), ("CAB-0.resS", &ress)] {
+                let mut node_reader = unity_fs.get_file_reader_by_path(&path.to_owned()).unwrap();
+                for start in [0usize, 60, 127, 250, 383] {
+                    // a single read spans the end of one block and the start of the next.
+                    node_reader.seek(SeekFrom::Start(start as u64)).unwrap();
+                    let mut chunk = [0u8; 10];
+                    assert_eq!(node_reader.read(&mut chunk).unwrap(), 10);
+                    assert_eq!(chunk, data[start..start + 10]);
+
+                    // and several blocks.
+                    node_reader.seek(SeekFrom::Start(start as u64)).unwrap();
+                    let mut chunk = [0u8; 100];
+                    assert_eq!(node_reader.read(&mut chunk).unwrap(), 100);
+                    assert_eq!(chunk, data[start..start + 100]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extract_all_to_dir() {
+        let cab: Vec<u8> = (0..10000u32).map(|i| (i % 13) as u8).collect();
+        let ress: Vec<u8> = (0..3000u32).map(|i| (i % 7) as u8).collect();
+        let build_bundle = |ress_path: &str| {
+            let mut bundle = Cursor::new(Vec::new());
+            writer::UnityFSBuilder::new()
+                .block_size(4096)
+                .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+                .add_node(ress_path, 0, Box::new(Cursor::new(ress.clone())))
+                .write(&mut bundle)
+                .unwrap();
+            UnityFS::read(Box::new(Cursor::new(bundle.into_inner())), None).unwrap()
+        };
+        let out_dir = std::env::temp_dir().join(format!("io_unity_extract_{}", std::process::id()));
+
+        let out_paths = build_bundle("res/CAB-0.resS")
+            .extract_all_to_dir(&out_dir)
+            .unwrap();
+        assert_eq!(
+            out_paths,
+            vec![
+                out_dir.join("CAB-0"),
+                out_dir.join("res").join("CAB-0.resS")
+            ]
+        );
+        assert_eq!(std::fs::read(&out_paths[0]).unwrap(), cab);
+        assert_eq!(std::fs::read(&out_paths[1]).unwrap(), ress);
+
+        let err = build_bundle("../CAB-0.resS")
+            .extract_all_to_dir(&out_dir)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        std::fs::remove_dir_all(out_dir).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use super::{Node, UnityFS};
+use rayon::prelude::*;
+use std::io::{prelude::*, ErrorKind, SeekFrom};
+use std::ops::Range;
+use std::sync::Arc;
+
+enum StorageBlockData {
+    Cached(Arc<Vec<u8>>),
+    Compressed(Vec<u8>),
+}
+
+impl UnityFS {
+    /// Uncompress all blocks of the node across the rayon thread pool.
+    pub fn par_get_file_data_by_path(&self, path: &String) -> std::io::Result<Vec<u8>> {
+        let node = self
+            .content
+            .blocks_info
+            .directory_info
+            .iter()
+            .find(|node| path == &node.path())
+            .ok_or(std::io::Error::from(ErrorKind::NotFound))?;
+        let block_range = self.get_node_block_range(node);
+        let blocks = self.par_read_storage_blocks(block_range.clone())?;
+        self.copy_node_data(node, block_range.start, &blocks)
+    }
+
+    /// Uncompress all blocks of the node across the rayon thread pool into the block cache,
+    /// so later reads of the node do not need to uncompress them.
+    /// Only useful when the block cache capacity is larger than the node.
+    pub fn par_prefetch_file_by_path(&self, path: &String) -> std::io::Result<()> {
+        let node = self
+            .content
+            .blocks_info
+            .directory_info
+            .iter()
+            .find(|node| path == &node.path())
+            .ok_or(std::io::Error::from(ErrorKind::NotFound))?;
+        self.par_read_storage_blocks(self.get_node_block_range(node))?;
+        Ok(())
+    }
+
+    /// Uncompress the whole archive across the rayon thread pool and return the data of every node.
+    /// The whole uncompressed archive is held in memory while the nodes are copied.
+    pub fn par_get_all_file_data(&self) -> std::io::Result<Vec<(String, Vec<u8>)>> {
+        let blocks =
+            self.par_read_storage_blocks(0..self.content.blocks_info.storage_blocks.len())?;
+        self.content
+            .blocks_info
+            .directory_info
+            .par_iter()
+            .map(|node| Ok((node.path(), self.copy_node_data(node, 0, &blocks)?)))
+            .collect()
+    }
+
+    /// Index range of the storage blocks which contain data of the node.
+    fn get_node_block_range(&self, node: &Node) -> Range<usize> {
+        let node_start = node.offset as u64;
+        let node_end = node_start + node.size as u64;
+        let storage_blocks = &self.content.blocks_info.storage_blocks;
+        let start = self
+            .storage_blocks_start_positions
+            .iter()
+            .zip(storage_blocks)
+            .position(|(&(_, uncompressed_data_offset), sb)| {
+                uncompressed_data_offset + sb.uncompressed_size as u64 > node_start
+            })
+            .unwrap_or(storage_blocks.len());
+        let end = self
+            .storage_blocks_start_positions
+            .iter()
+            .position(|&(_, uncompressed_data_offset)| uncompressed_data_offset >= node_end)
+            .unwrap_or(storage_blocks.len())
+            .max(start);
+        start..end
+    }
+
+    /// Read the compressed blocks in one pass, then decrypt and uncompress them in parallel.
+    /// Blocks found in the block cache are not uncompressed again.
+    fn par_read_storage_blocks(
+        &self,
+        block_range: Range<usize>,
+    ) -> std::io::Result<Vec<Arc<Vec<u8>>>> {
+        let blocks = {
+            let mut block_cache = self.block_cache.lock().unwrap();
+            block_range
+                .map(|block_index| (block_index, block_cache.get(block_index)))
+                .collect::<Vec<_>>()
+        };
+
+        // the reader is only locked while the compressed blocks are read,
+        // they are uncompressed without any lock held.
+        let blocks = {
+            let mut file_reader = self
+                .file_reader
+                .lock()
+                .map_err(|_| std::io::Error::from(ErrorKind::BrokenPipe))?;
+            blocks
+                .into_iter()
+                .map(|(block_index, cache_block)| {
+                    if let Some(cache_block) = cache_block {
+                        return Ok((block_index, StorageBlockData::Cached(cache_block)));
+                    }
+                    let sb = &self.content.blocks_info.storage_blocks[block_index];
+                    let (compressed_data_offset, _) =
+                        self.storage_blocks_start_positions[block_index];
+                    let mut blocks_compressedd_stream = vec![0u8; sb.compressed_size as usize];
+                    file_reader.seek(SeekFrom::Start(
+                        compressed_data_offset + self.content.position,
+                    ))?;
+                    file_reader.read_exact(&mut blocks_compressedd_stream)?;
+                    Ok((
+                        block_index,
+                        StorageBlockData::Compressed(blocks_compressedd_stream),
+                    ))
+                })
+                .collect::<std::io::Result<Vec<_>>>()?
+        };
+
+        let blocks = blocks
+            .into_par_iter()
+            .map(|(block_index, block)| {
+                let blocks_compressedd_stream = match block {
+                    StorageBlockData::Cached(cache_block) => {
+                        return Ok((block_index, cache_block, true))
+                    }
+                    StorageBlockData::Compressed(blocks_compressedd_stream) => {
+                        blocks_compressedd_stream
+                    }
+                };
+                let sb = &self.content.blocks_info.storage_blocks[block_index];
+                let blocks_compressedd_stream = match &self.decryptor {
+                    Some(decryptor) => decryptor.decrypt_block(
+                        block_index,
+                        sb.flags.compression_id(),
+                        blocks_compressedd_stream,
+                    )?,
+                    None => blocks_compressedd_stream,
+                };
+                let blocks_uncompressedd_stream = self.block_codecs.block_uncompressed(
+                    sb.uncompressed_size as u64,
+                    sb.flags.compression_id(),
+                    blocks_compressedd_stream,
+                )?;
+                Ok((block_index, Arc::new(blocks_uncompressedd_stream), false))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let mut block_cache = self.block_cache.lock().unwrap();
+        Ok(blocks
+            .into_iter()
+            .map(|(block_index, block, is_cached)| {
+                if !is_cached {
+                    block_cache.insert(block_index, block.clone());
+                }
+                block
+            })
+            .collect())
+    }
+
+    /// Copy node data out of `blocks`, which are uncompressed blocks starting at `first_block_index`.
+    fn copy_node_data(
+        &self,
+        node: &Node,
+        first_block_index: usize,
+        blocks: &[Arc<Vec<u8>>],
+    ) -> std::io::Result<Vec<u8>> {
+        let node_start = node.offset as u64;
+        let node_end = node_start + node.size as u64;
+        let mut file_data = Vec::with_capacity(node.size as usize);
+        for (block_index, block) in (first_block_index..).zip(blocks) {
+            let (_, uncompressed_data_offset) = self.storage_blocks_start_positions[block_index];
+            let block_end = uncompressed_data_offset + block.len() as u64;
+            if block_end <= node_start || uncompressed_data_offset >= node_end {
+                continue;
+            }
+            let copy_start = node_start.max(uncompressed_data_offset) - uncompressed_data_offset;
+            let copy_end = node_end.min(block_end) - uncompressed_data_offset;
+            file_data.extend_from_slice(&block[copy_start as usize..copy_end as usize]);
+        }
+        if file_data.len() != node.size as usize {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "node data is out of storage blocks",
+            ));
+        }
+        Ok(file_data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{writer::UnityFSBuilder, CompressionType};
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_par_get_file_data() {
+        let cab: Vec<u8> = (0..50000u32).map(|i| (i % 13) as u8).collect();
+        let ress: Vec<u8> = (0..30000u32).map(|i| (i % 7) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        UnityFSBuilder::new()
+            .block_compression(CompressionType::Lzma)
+            .block_size(4096)
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+            .add_node("CAB-0.resS", 0, Box::new(Cursor::new(ress.clone())))
+            .add_node("empty", 0, Box::new(Cursor::new(Vec::new())))
+            .write(&mut bundle)
+            .unwrap();
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.into_inner())), None).unwrap();
+        unity_fs.set_block_cache_capacity(0);
+
+        assert_eq!(
+            unity_fs
+                .par_get_file_data_by_path(&"CAB-0.resS".to_owned())
+                .unwrap(),
+            ress
+        );
+        assert_eq!(
+            unity_fs.par_get_all_file_data().unwrap(),
+            vec![
+                ("CAB-0".to_owned(), cab.clone()),
+                ("CAB-0.resS".to_owned(), ress),
+                ("empty".to_owned(), Vec::new()),
+            ]
+        );
+
+        unity_fs.set_block_cache_capacity(usize::MAX);
+        unity_fs
+            .par_prefetch_file_by_path(&"CAB-0".to_owned())
+            .unwrap();
+        assert_eq!(
+            unity_fs.block_cache.lock().unwrap().get_size(),
+            // blocks contain cab and the block shared with resS.
+            4096 * cab.len().div_ceil(4096)
+        );
+        assert_eq!(
+            unity_fs.get_file_data_by_path(&"CAB-0".to_owned()).unwrap(),
+            cab
+        );
+    }
+}
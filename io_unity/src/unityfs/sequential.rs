@@ -0,0 +1,244 @@
+use super::{ArchiveFlags, UnityFS};
+use binrw::{BinRead, BinResult, NullString};
+use std::collections::VecDeque;
+use std::io::{prelude::*, Cursor, ErrorKind, SeekFrom};
+
+/// Default budget of data kept behind the read position of a [`SequentialResource`].
+pub const DEFAULT_SEQUENTIAL_BUFFER_CAPACITY: usize = 16 * 1024 * 1024;
+
+/// Seekable view of a forward-only reader, such as stdin or a download stream.
+///
+/// Data pulled from the reader is kept in a buffer of at most `capacity` bytes,
+/// seeking forward reads and buffers the skipped data,
+/// seeking back is only possible inside the buffer and fails with [`ErrorKind::Unsupported`] otherwise.
+pub struct SequentialResource<R: Read> {
+    reader: R,
+    capacity: usize,
+    buffer: VecDeque<u8>,
+    buffer_start: u64,
+    current_position: u64,
+    is_eof: bool,
+}
+
+impl<R: Read> SequentialResource<R> {
+    pub fn new(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            capacity,
+            buffer: VecDeque::new(),
+            buffer_start: 0,
+            current_position: 0,
+            is_eof: false,
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn get_buffer_end(&self) -> u64 {
+        self.buffer_start + self.buffer.len() as u64
+    }
+
+    /// Pull data from the reader until `position` is buffered or the reader ends.
+    fn fill_to(&mut self, position: u64) -> std::io::Result<()> {
+        let mut chunk = [0u8; 8192];
+        while !self.is_eof && self.get_buffer_end() < position {
+            let read_size = match self.reader.read(&mut chunk) {
+                Ok(read_size) => read_size,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if read_size == 0 {
+                self.is_eof = true;
+            }
+            self.buffer.extend(&chunk[..read_size]);
+            // keep the data at the read position, even if it is out of capacity.
+            let keep_start = self.current_position.min(self.get_buffer_end());
+            while self.buffer.len() > self.capacity && self.buffer_start < keep_start {
+                let drop_size = (self.buffer.len() - self.capacity)
+                    .min((keep_start - self.buffer_start) as usize);
+                self.buffer.drain(..drop_size);
+                self.buffer_start += drop_size as u64;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for SequentialResource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.current_position < self.buffer_start {
+            return Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "read position is dropped from the buffer",
+            ));
+        }
+        self.fill_to(self.current_position + buf.len() as u64)?;
+        let buffer_offset = (self.current_position - self.buffer_start) as usize;
+        if buffer_offset >= self.buffer.len() {
+            return Ok(0);
+        }
+        let read_size = buf.len().min(self.buffer.len() - buffer_offset);
+        for (dst, src) in buf
+            .iter_mut()
+            .zip(self.buffer.range(buffer_offset..buffer_offset + read_size))
+        {
+            *dst = *src;
+        }
+        self.current_position += read_size as u64;
+        Ok(read_size)
+    }
+}
+
+impl<R: Read> Seek for SequentialResource<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::Current(offset) => self.current_position as i64 + offset,
+            SeekFrom::End(offset) => {
+                self.fill_to(u64::MAX)?;
+                self.get_buffer_end() as i64 + offset
+            }
+        };
+        if new_pos < 0 {
+            return Err(std::io::Error::from(ErrorKind::InvalidInput));
+        }
+        if (new_pos as u64) < self.buffer_start {
+            return Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "can not seek back out of the buffer",
+            ));
+        }
+        self.current_position = new_pos as u64;
+        Ok(self.current_position)
+    }
+}
+
+impl UnityFS {
+    /// Read archive from a forward-only reader.
+    ///
+    /// Header and blocks info are read sequentially and storage blocks are read on demand
+    /// through a [`SequentialResource`], so nodes should be read in the order of their data,
+    /// as [`UnityFS::extract_all_to_dir`] does.
+    /// Archives with blocks info at the end and legacy archives are buffered in memory as a whole.
+    /// Node data of UnityFS archives is not read when the archive is read,
+    /// so the first node is still in the buffer whatever size the following nodes are.
+    pub fn read_sequential<R: Read + Send + 'static>(
+        reader: R,
+        resource_search_path: Option<String>,
+    ) -> BinResult<UnityFS> {
+        let mut file = SequentialResource::new(reader, DEFAULT_SEQUENTIAL_BUFFER_CAPACITY);
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        let need_full_buffering = &magic != b"UnityFS\0" || {
+            let _version = u32::read_be(&mut file)?;
+            let _unity_version = NullString::read(&mut file)?;
+            let _unity_revision = NullString::read(&mut file)?;
+            let _size = i64::read_be(&mut file)?;
+            let _compressed_blocks_info_size = u32::read_be(&mut file)?;
+            let _uncompressed_blocks_info_size = u32::read_be(&mut file)?;
+            ArchiveFlags::read_be(&mut file)?.blocks_info_at_the_end()
+        };
+        file.seek(SeekFrom::Start(0))?;
+        if need_full_buffering {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            return Self::read(Box::new(Cursor::new(data)), resource_search_path);
+        }
+        Self::read(Box::new(file), resource_search_path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{writer::UnityFSBuilder, CompressionType};
+    use super::*;
+
+    /// Reader which hand out a few bytes on every read, like a pipe.
+    struct Pipe(Cursor<Vec<u8>>);
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read_size = buf.len().min(100);
+            self.0.read(&mut buf[..read_size])
+        }
+    }
+
+    #[test]
+    fn test_sequential_resource() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        let mut resource = SequentialResource::new(Pipe(Cursor::new(data.clone())), 1000);
+        let mut buff = [0u8; 20];
+        resource.seek(SeekFrom::Start(3000)).unwrap();
+        resource.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, data[3000..3020]);
+        resource.seek(SeekFrom::Current(-500)).unwrap();
+        resource.read_exact(&mut buff).unwrap();
+        assert_eq!(buff, data[2520..2540]);
+        assert_eq!(
+            resource.seek(SeekFrom::Start(0)).unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+        assert_eq!(resource.seek(SeekFrom::End(0)).unwrap(), 5000);
+        assert_eq!(resource.read(&mut buff).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_read_sequential() {
+        let cab: Vec<u8> = (0..50000u32).map(|i| (i % 13) as u8).collect();
+        let ress: Vec<u8> = (0..30000u32).map(|i| (i % 7) as u8).collect();
+        for at_the_end in [false, true] {
+            let mut bundle = Cursor::new(Vec::new());
+            UnityFSBuilder::new()
+                .block_compression(CompressionType::Lz4)
+                .block_size(4096)
+                .blocks_info_at_the_end(at_the_end)
+                .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+                .add_node("CAB-0.resS", 0, Box::new(Cursor::new(ress.clone())))
+                .write(&mut bundle)
+                .unwrap();
+            let unity_fs =
+                UnityFS::read_sequential(Pipe(Cursor::new(bundle.into_inner())), None).unwrap();
+            for (path, data) in [("CAB-0", &cab), ("CAB-0.resS", &ress)] {
+                let mut node_reader = unity_fs.get_file_reader_by_path(&path.to_owned()).unwrap();
+                let mut node_data = Vec::new();
+                node_reader.read_to_end(&mut node_data).unwrap();
+                assert_eq!(&node_data, data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_sequential_larger_than_buffer() {
+        let cab: Vec<u8> = (0..50000u32).map(|i| (i % 13) as u8).collect();
+        let ress_size = DEFAULT_SEQUENTIAL_BUFFER_CAPACITY + 4 * 1024 * 1024;
+        let ress: Vec<u8> = (0..ress_size).map(|i| (i % 7) as u8).collect();
+        let resource: Vec<u8> = (0..3000u32).map(|i| (i % 5) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        UnityFSBuilder::new()
+            .block_compression(CompressionType::None)
+            .block_size(1024 * 1024)
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab.clone())))
+            .add_node("CAB-0.resS", 0, Box::new(Cursor::new(ress.clone())))
+            .add_node("CAB-0.resource", 0, Box::new(Cursor::new(resource.clone())))
+            .write(&mut bundle)
+            .unwrap();
+        let bundle = bundle.into_inner();
+        assert!(bundle.len() > DEFAULT_SEQUENTIAL_BUFFER_CAPACITY);
+
+        let unity_fs = UnityFS::read_sequential(Cursor::new(bundle), None).unwrap();
+        assert_eq!(unity_fs.get_cab_path(), vec!["CAB-0".to_owned()]);
+        // the trailing node starts out of the buffer, but it is not read until the nodes before it.
+        for (path, data) in [
+            ("CAB-0", &cab),
+            ("CAB-0.resS", &ress),
+            ("CAB-0.resource", &resource),
+        ] {
+            let mut node_reader = unity_fs.get_file_reader_by_path(&path.to_owned()).unwrap();
+            let mut node_data = Vec::new();
+            node_reader.read_to_end(&mut node_data).unwrap();
+            assert_eq!(&node_data, data);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use super::{read_storage_block, UnityFS};
+use std::io::SeekFrom;
+use thiserror::Error;
+
+/// Corruption found by [`UnityFS::verify`].
+#[derive(Error, Debug)]
+pub enum IntegrityError {
+    #[error("archive size {declared_size} is larger than file size {file_size}")]
+    ArchiveTruncated { declared_size: i64, file_size: u64 },
+    #[error("storage block {block_index} has negative compressed size {compressed_size}")]
+    InvalidBlockSize {
+        block_index: usize,
+        compressed_size: i32,
+    },
+    #[error(
+        "storage block {block_index} end at {block_end} which is out of file size {file_size}"
+    )]
+    BlockOutOfFile {
+        block_index: usize,
+        block_end: u64,
+        file_size: u64,
+    },
+    #[error("node {path:?} (offset {offset}, size {size}) is out of uncompressed data size {uncompressed_data_size}")]
+    NodeOutOfRange {
+        path: String,
+        offset: i64,
+        size: i64,
+        uncompressed_data_size: u64,
+    },
+    #[error("storage block {block_index} can not be uncompressed : {source}")]
+    BlockCorrupted {
+        block_index: usize,
+        source: std::io::Error,
+    },
+    #[error("storage block {block_index} uncompressed to {actual_size} bytes, expect {expected_size} bytes")]
+    BlockSizeMismatch {
+        block_index: usize,
+        expected_size: u32,
+        actual_size: usize,
+    },
+    #[error("IO error while verify : {0}")]
+    IOError(#[from] std::io::Error),
+}
+
+impl UnityFS {
+    /// Check the archive against the length of the resource:
+    /// storage blocks must lie inside the file and nodes inside the uncompressed data.
+    /// With `verify_blocks_data`, every storage block is also uncompressed and its size checked,
+    /// which cost as much as reading the whole archive.
+    ///
+    /// `uncompressed_data_hash` is not checked, unity usually write it as zeros
+    /// and the hash function of the others is not known.
+    pub fn verify(&self, verify_blocks_data: bool) -> Result<(), Vec<IntegrityError>> {
+        let mut errors = Vec::new();
+        let file_size = match self.file_reader.lock() {
+            Ok(mut file_reader) => file_reader.seek(SeekFrom::End(0)),
+            Err(_) => Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe)),
+        }
+        .map_err(|err| vec![IntegrityError::from(err)])?;
+
+        if self.content.size < 0 || self.content.size as u64 > file_size {
+            errors.push(IntegrityError::ArchiveTruncated {
+                declared_size: self.content.size,
+                file_size,
+            });
+        }
+
+        let storage_blocks = &self.content.blocks_info.storage_blocks;
+        let mut uncompressed_data_size = 0u64;
+        let mut block_end = self.content.position;
+        let mut blocks_in_file = Vec::with_capacity(storage_blocks.len());
+        for (block_index, sb) in storage_blocks.iter().enumerate() {
+            uncompressed_data_size += sb.uncompressed_size as u64;
+            if sb.compressed_size < 0 {
+                errors.push(IntegrityError::InvalidBlockSize {
+                    block_index,
+                    compressed_size: sb.compressed_size,
+                });
+                blocks_in_file.push(false);
+                continue;
+            }
+            block_end += sb.compressed_size as u64;
+            if block_end > file_size {
+                errors.push(IntegrityError::BlockOutOfFile {
+                    block_index,
+                    block_end,
+                    file_size,
+                });
+            }
+            blocks_in_file.push(block_end <= file_size);
+        }
+
+        for node in &self.content.blocks_info.directory_info {
+            if node.offset < 0
+                || node.size < 0
+                || node.offset as u64 + node.size as u64 > uncompressed_data_size
+            {
+                errors.push(IntegrityError::NodeOutOfRange {
+                    path: node.path(),
+                    offset: node.offset,
+                    size: node.size,
+                    uncompressed_data_size,
+                });
+            }
+        }
+
+        if verify_blocks_data {
+            for (block_index, sb) in storage_blocks.iter().enumerate() {
+                if !blocks_in_file[block_index] {
+                    continue;
+                }
+                let (compressed_data_offset, _) = self.storage_blocks_start_positions[block_index];
+                match read_storage_block(
+                    &self.file_reader,
+                    compressed_data_offset + self.content.position,
+                    block_index,
+                    sb,
+                    &self.decryptor,
+                    &self.block_codecs,
+                    &self.block_cache,
+                ) {
+                    Ok(block) if block.len() != sb.uncompressed_size as usize => {
+                        errors.push(IntegrityError::BlockSizeMismatch {
+                            block_index,
+                            expected_size: sb.uncompressed_size,
+                            actual_size: block.len(),
+                        })
+                    }
+                    Ok(_) => (),
+                    Err(source) => errors.push(IntegrityError::BlockCorrupted {
+                        block_index,
+                        source,
+                    }),
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{writer::UnityFSBuilder, CompressionType};
+    use super::*;
+    use std::io::Cursor;
+
+    fn build_bundle() -> Vec<u8> {
+        let cab: Vec<u8> = (0..10000u32).map(|i| (i % 13) as u8).collect();
+        let mut bundle = Cursor::new(Vec::new());
+        UnityFSBuilder::new()
+            .block_compression(CompressionType::Lz4)
+            .blocks_info_compression(CompressionType::None)
+            .block_size(4096)
+            .add_node("CAB-0", 4, Box::new(Cursor::new(cab)))
+            .write(&mut bundle)
+            .unwrap();
+        bundle.into_inner()
+    }
+
+    #[test]
+    fn test_verify() {
+        let bundle = build_bundle();
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(bundle.clone())), None).unwrap();
+        assert!(unity_fs.verify(true).is_ok());
+        let data_start = unity_fs.content.position as usize;
+
+        let mut truncated = bundle.clone();
+        truncated.truncate(bundle.len() - 10);
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(truncated)), None).unwrap();
+        let errors = unity_fs.verify(false).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [
+                IntegrityError::ArchiveTruncated { .. },
+                IntegrityError::BlockOutOfFile { block_index: 2, .. }
+            ]
+        ));
+
+        let mut corrupted = bundle;
+        corrupted[data_start..data_start + 8].fill(0xff);
+        let unity_fs = UnityFS::read(Box::new(Cursor::new(corrupted)), None).unwrap();
+        assert!(unity_fs.verify(false).is_ok());
+        let errors = unity_fs.verify(true).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [IntegrityError::BlockCorrupted { block_index: 0, .. }
+                | IntegrityError::BlockSizeMismatch { block_index: 0, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_verify_ignore_data_hash() {
+        let bundle = build_bundle();
+        let mut unity_fs = UnityFS::read(Box::new(Cursor::new(bundle)), None).unwrap();
+        unity_fs.content.blocks_info.uncompressed_data_hash = [0xab; 16];
+        assert!(unity_fs.verify(true).is_ok());
+    }
+}
@@ -0,0 +1,244 @@
+use super::{
+    codec::BlockCodecs, ArchiveFlags, BlocksInfo, CompressionType, Node, StorageBlock,
+    StorageBlockFlags, UnityFSFile, UnityResource,
+};
+use binrw::{BinResult, BinWrite, NullString};
+use std::io::{prelude::*, Cursor, SeekFrom};
+use std::sync::Arc;
+
+/// Build a UnityFS archive from named nodes.
+///
+/// All nodes are packed into one uncompressed data stream which is then split into
+/// storage blocks of `block_size` and compressed with `block_compression`.
+/// The compressed blocks are buffered in memory until the archive is written.
+pub struct UnityFSBuilder {
+    version: u32,
+    unity_version: String,
+    unity_revision: String,
+    block_compression: CompressionType,
+    blocks_info_compression: CompressionType,
+    block_size: u32,
+    blocks_info_at_the_end: bool,
+    block_info_need_padding_at_start: bool,
+    block_codecs: Arc<BlockCodecs>,
+    nodes: Vec<(String, u32, Box<dyn UnityResource + Send>)>,
+}
+
+impl Default for UnityFSBuilder {
+    fn default() -> Self {
+        Self {
+            version: 6,
+            unity_version: "5.x.x".to_owned(),
+            unity_revision: "2018.4.0f1".to_owned(),
+            block_compression: CompressionType::Lz4HC,
+            blocks_info_compression: CompressionType::Lz4HC,
+            block_size: 0x20000,
+            blocks_info_at_the_end: false,
+            block_info_need_padding_at_start: false,
+            block_codecs: BlockCodecs::get_default(),
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl UnityFSBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// UnityFS format version. version 7 and above align the blocks info to 16 bytes.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn unity_version(mut self, unity_version: &str) -> Self {
+        self.unity_version = unity_version.to_owned();
+        self
+    }
+
+    pub fn unity_revision(mut self, unity_revision: &str) -> Self {
+        self.unity_revision = unity_revision.to_owned();
+        self
+    }
+
+    pub fn block_compression(mut self, compression_type: CompressionType) -> Self {
+        self.block_compression = compression_type;
+        self
+    }
+
+    pub fn blocks_info_compression(mut self, compression_type: CompressionType) -> Self {
+        self.blocks_info_compression = compression_type;
+        self
+    }
+
+    /// Uncompressed size of each storage block. Use `u32::MAX` to put all data in one block.
+    pub fn block_size(mut self, block_size: u32) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    pub fn blocks_info_at_the_end(mut self, blocks_info_at_the_end: bool) -> Self {
+        self.blocks_info_at_the_end = blocks_info_at_the_end;
+        self
+    }
+
+    pub fn block_info_need_padding_at_start(
+        mut self,
+        block_info_need_padding_at_start: bool,
+    ) -> Self {
+        self.block_info_need_padding_at_start = block_info_need_padding_at_start;
+        self
+    }
+
+    /// Codecs used to compress blocks, the built-in codecs are used by default.
+    pub fn block_codecs(mut self, block_codecs: Arc<BlockCodecs>) -> Self {
+        self.block_codecs = block_codecs;
+        self
+    }
+
+    /// Add a node to the archive. `flags` is stored as is, unity use 4 for serialized files.
+    pub fn add_node(
+        mut self,
+        path: &str,
+        flags: u32,
+        node_reader: Box<dyn UnityResource + Send>,
+    ) -> Self {
+        self.nodes.push((path.to_owned(), flags, node_reader));
+        self
+    }
+
+    pub fn write<W: Write + Seek>(self, writer: &mut W) -> BinResult<()> {
+        let storage_block_flags =
+            StorageBlockFlags::new().with_compression_id(self.block_compression as u8);
+        let mut storage_blocks = Vec::new();
+        let mut compressed_blocks = Vec::new();
+        let mut directory_info = Vec::new();
+
+        let mut block_buff = Vec::new();
+        let mut push_block = |block_buff: Vec<u8>| -> std::io::Result<()> {
+            let uncompressed_size = block_buff.len() as u32;
+            let compressed_block = self
+                .block_codecs
+                .block_compressed(self.block_compression as u8, block_buff)?;
+            storage_blocks.push(StorageBlock {
+                uncompressed_size,
+                compressed_size: compressed_block.len() as i32,
+                flags: storage_block_flags,
+            });
+            compressed_blocks.push(compressed_block);
+            Ok(())
+        };
+
+        let mut uncompressed_data_offset = 0u64;
+        for (path, flags, mut node_reader) in self.nodes {
+            let size = node_reader.seek(SeekFrom::End(0))?;
+            node_reader.seek(SeekFrom::Start(0))?;
+            directory_info.push(Node {
+                offset: uncompressed_data_offset as i64,
+                size: size as i64,
+                flags,
+                path: NullString::from(path.as_str()),
+            });
+            uncompressed_data_offset += size;
+
+            let mut remain = size;
+            while remain > 0 {
+                let want = (self.block_size as u64 - block_buff.len() as u64).min(remain);
+                let start = block_buff.len();
+                block_buff.resize(start + want as usize, 0);
+                node_reader.read_exact(&mut block_buff[start..])?;
+                remain -= want;
+                if block_buff.len() == self.block_size as usize {
+                    push_block(std::mem::take(&mut block_buff))?;
+                }
+            }
+        }
+        if !block_buff.is_empty() {
+            push_block(block_buff)?;
+        }
+
+        let blocks_info = BlocksInfo {
+            uncompressed_data_hash: [0u8; 16],
+            blocks_info_count: storage_blocks.len() as u32,
+            storage_blocks,
+            nodes_count: directory_info.len() as u32,
+            directory_info,
+        };
+        let mut blocks_info_writer = Cursor::new(Vec::new());
+        blocks_info.write(&mut blocks_info_writer)?;
+        let blocks_info_uncompressedd_stream = blocks_info_writer.into_inner();
+        let uncompressed_blocks_info_size = blocks_info_uncompressedd_stream.len() as u32;
+        let blocks_info_compressedd_stream = self.block_codecs.block_compressed(
+            self.blocks_info_compression as u8,
+            blocks_info_uncompressedd_stream,
+        )?;
+        let compressed_blocks_info_size = blocks_info_compressedd_stream.len() as u32;
+
+        let align_16 = |pos: u64| (pos + 15) & !15;
+        let header_size = 8
+            + 4
+            + (self.unity_version.len() as u64 + 1)
+            + (self.unity_revision.len() as u64 + 1)
+            + 8
+            + 4
+            + 4
+            + 4;
+        let mut size = header_size;
+        if self.version >= 7 {
+            size = align_16(size);
+        }
+        if !self.blocks_info_at_the_end {
+            size += compressed_blocks_info_size as u64;
+        }
+        if self.block_info_need_padding_at_start {
+            size = align_16(size);
+        }
+        size += compressed_blocks
+            .iter()
+            .map(|b| b.len() as u64)
+            .sum::<u64>();
+        if self.blocks_info_at_the_end {
+            size += compressed_blocks_info_size as u64;
+        }
+
+        let file = UnityFSFile {
+            version: self.version,
+            unity_version: NullString::from(self.unity_version.as_str()),
+            unity_revision: NullString::from(self.unity_revision.as_str()),
+            size: size as i64,
+            compressed_blocks_info_size,
+            uncompressed_blocks_info_size,
+            flags: ArchiveFlags::new()
+                .with_compression_id(self.blocks_info_compression as u8)
+                .with_blocks_and_directory_info_combined(true)
+                .with_blocks_info_at_the_end(self.blocks_info_at_the_end)
+                .with_block_info_need_padding_at_start(self.block_info_need_padding_at_start),
+            blocks_info,
+            position: 0,
+        };
+
+        let start_pos = writer.stream_position()?;
+        let write_padding = |writer: &mut W| -> std::io::Result<()> {
+            let pos = writer.stream_position()? - start_pos;
+            writer.write_all(&vec![0u8; (align_16(pos) - pos) as usize])
+        };
+        file.write(writer)?;
+        if self.version >= 7 {
+            write_padding(writer)?;
+        }
+        if !self.blocks_info_at_the_end {
+            writer.write_all(&blocks_info_compressedd_stream)?;
+        }
+        if self.block_info_need_padding_at_start {
+            write_padding(writer)?;
+        }
+        for compressed_block in compressed_blocks {
+            writer.write_all(&compressed_block)?;
+        }
+        if self.blocks_info_at_the_end {
+            writer.write_all(&blocks_info_compressedd_stream)?;
+        }
+        Ok(())
+    }
+}
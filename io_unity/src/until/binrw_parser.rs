@@ -16,6 +16,12 @@ pub fn position_parser() -> BinResult<u64> {
 #[derive(Debug, PartialEq, Clone)]
 pub struct U8Bool(bool);
 
+impl From<bool> for U8Bool {
+    fn from(value: bool) -> Self {
+        U8Bool(value)
+    }
+}
+
 impl Deref for U8Bool {
     type Target = bool;
 
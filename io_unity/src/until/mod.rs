@@ -8,6 +8,7 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UnityVersion {
     version: Vec<u32>,
     build_type: Option<String>,
@@ -28,8 +29,8 @@ impl UnityVersion {
         Ok(UnityVersion {
             version: VERSION_REGEX
                 .split(version)
-                .map(|d| d.parse::<u32>().unwrap())
-                .collect(),
+                .map(|d| d.parse::<u32>())
+                .collect::<Result<_, _>>()?,
             build_type: BUILD_TYPE_REGEX
                 .captures(version)
                 .and_then(|c| c.get(0))
@@ -49,6 +50,7 @@ impl UnityVersion {
 #[binrw]
 #[brw(repr = u8)]
 #[derive(Debug, Eq, PartialEq, TryFromPrimitive, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u8)]
 pub enum Endian {
     Little,
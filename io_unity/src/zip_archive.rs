@@ -0,0 +1,415 @@
+use crate::split_resource::SplitResource;
+use crate::unityfs::{ResourceWindow, UnityResource};
+use binrw::{binrw, BinRead, BinResult};
+use std::collections::BTreeMap;
+use std::io::{prelude::*, Cursor, ErrorKind, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+/// End of central directory record is 22 bytes followed by a comment of at most 65535 bytes.
+const END_OF_CENTRAL_DIRECTORY_SEARCH_SIZE: u64 = 22 + u16::MAX as u64;
+pub(crate) const COMPRESSION_METHOD_STORED: u16 = 0;
+pub(crate) const COMPRESSION_METHOD_DEFLATED: u16 = 8;
+
+/// Zip file such as APK and OBB, whose entries can be read as [`UnityResource`].
+/// Stored entries are read in place, deflated entries are uncompressed into memory.
+pub struct ZipArchive {
+    file_reader: Arc<Mutex<Box<dyn UnityResource + Send>>>,
+    entries: BTreeMap<String, ZipEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZipEntry {
+    pub compression_method: u16,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub local_header_offset: u64,
+}
+
+#[binrw]
+#[brw(little, magic = b"PK\x05\x06")]
+#[derive(Clone, Debug, PartialEq)]
+struct EndOfCentralDirectory {
+    disk_number: u16,
+    central_directory_disk_number: u16,
+    disk_entries_count: u16,
+    entries_count: u16,
+    central_directory_size: u32,
+    central_directory_offset: u32,
+    comment_length: u16,
+}
+
+#[binrw]
+#[brw(little, magic = b"PK\x06\x07")]
+#[derive(Clone, Debug, PartialEq)]
+struct Zip64EndOfCentralDirectoryLocator {
+    central_directory_disk_number: u32,
+    end_of_central_directory_offset: u64,
+    disks_count: u32,
+}
+
+#[binrw]
+#[brw(little, magic = b"PK\x06\x06")]
+#[derive(Clone, Debug, PartialEq)]
+struct Zip64EndOfCentralDirectory {
+    record_size: u64,
+    version_made_by: u16,
+    version_needed: u16,
+    disk_number: u32,
+    central_directory_disk_number: u32,
+    disk_entries_count: u64,
+    entries_count: u64,
+    central_directory_size: u64,
+    central_directory_offset: u64,
+}
+
+#[binrw]
+#[brw(little, magic = b"PK\x01\x02")]
+#[derive(Clone, Debug, PartialEq)]
+struct CentralDirectoryHeader {
+    version_made_by: u16,
+    version_needed: u16,
+    flags: u16,
+    compression_method: u16,
+    last_modified_time: u16,
+    last_modified_date: u16,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    file_name_length: u16,
+    extra_field_length: u16,
+    file_comment_length: u16,
+    disk_number_start: u16,
+    internal_file_attributes: u16,
+    external_file_attributes: u32,
+    local_header_offset: u32,
+    #[br(args { count: file_name_length as usize, inner: () })]
+    file_name: Vec<u8>,
+    #[br(args { count: extra_field_length as usize, inner: () })]
+    extra_field: Vec<u8>,
+    #[br(args { count: file_comment_length as usize, inner: () })]
+    file_comment: Vec<u8>,
+}
+
+#[binrw]
+#[brw(little, magic = b"PK\x03\x04")]
+#[derive(Clone, Debug, PartialEq)]
+struct LocalFileHeader {
+    version_needed: u16,
+    flags: u16,
+    compression_method: u16,
+    last_modified_time: u16,
+    last_modified_date: u16,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    file_name_length: u16,
+    extra_field_length: u16,
+}
+
+impl CentralDirectoryHeader {
+    /// Sizes and offset are replaced by the zip64 extended information extra field
+    /// when they are `u32::MAX`.
+    fn to_zip_entry(&self) -> ZipEntry {
+        let mut entry = ZipEntry {
+            compression_method: self.compression_method,
+            compressed_size: self.compressed_size as u64,
+            uncompressed_size: self.uncompressed_size as u64,
+            local_header_offset: self.local_header_offset as u64,
+        };
+        let mut extra_field = self.extra_field.as_slice();
+        while extra_field.len() >= 4 {
+            let header_id = u16::from_le_bytes([extra_field[0], extra_field[1]]);
+            let data_size = u16::from_le_bytes([extra_field[2], extra_field[3]]) as usize;
+            let data = &extra_field[4..(4 + data_size).min(extra_field.len())];
+            if header_id == 0x0001 {
+                let mut values = data
+                    .chunks_exact(8)
+                    .map(|value| u64::from_le_bytes(value.try_into().unwrap()));
+                for (field, value) in [
+                    (&mut entry.uncompressed_size, self.uncompressed_size),
+                    (&mut entry.compressed_size, self.compressed_size),
+                    (&mut entry.local_header_offset, self.local_header_offset),
+                ] {
+                    if value == u32::MAX {
+                        if let Some(zip64_value) = values.next() {
+                            *field = zip64_value;
+                        }
+                    }
+                }
+            }
+            extra_field = &extra_field[(4 + data_size).min(extra_field.len())..];
+        }
+        entry
+    }
+}
+
+impl ZipArchive {
+    pub fn read(mut file: Box<dyn UnityResource + Send>) -> BinResult<ZipArchive> {
+        let file_size = file.seek(SeekFrom::End(0))?;
+        let search_start = file_size.saturating_sub(END_OF_CENTRAL_DIRECTORY_SEARCH_SIZE);
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(search_start))?;
+        file.read_to_end(&mut tail)?;
+        let end_of_central_directory_position = tail
+            .windows(4)
+            .rposition(|magic| magic == b"PK\x05\x06")
+            .ok_or(std::io::Error::new(
+                ErrorKind::InvalidData,
+                "can not find end of central directory",
+            ))?;
+        let end_of_central_directory = EndOfCentralDirectory::read(&mut Cursor::new(
+            &tail[end_of_central_directory_position..],
+        ))?;
+
+        let mut entries_count = end_of_central_directory.entries_count as u64;
+        let mut central_directory_offset = end_of_central_directory.central_directory_offset as u64;
+        if end_of_central_directory.entries_count == u16::MAX
+            || end_of_central_directory.central_directory_offset == u32::MAX
+        {
+            let locator =
+                end_of_central_directory_position
+                    .checked_sub(20)
+                    .and_then(|locator_position| {
+                        Zip64EndOfCentralDirectoryLocator::read(&mut Cursor::new(
+                            &tail[locator_position..],
+                        ))
+                        .ok()
+                    });
+            if let Some(locator) = locator {
+                file.seek(SeekFrom::Start(locator.end_of_central_directory_offset))?;
+                let zip64_end_of_central_directory = Zip64EndOfCentralDirectory::read(&mut file)?;
+                entries_count = zip64_end_of_central_directory.entries_count;
+                central_directory_offset = zip64_end_of_central_directory.central_directory_offset;
+            }
+        }
+
+        file.seek(SeekFrom::Start(central_directory_offset))?;
+        let mut entries = BTreeMap::new();
+        for _ in 0..entries_count {
+            let header = CentralDirectoryHeader::read(&mut file)?;
+            let path = String::from_utf8_lossy(&header.file_name).into_owned();
+            if !path.ends_with('/') {
+                entries.insert(path, header.to_zip_entry());
+            }
+        }
+        Ok(ZipArchive {
+            file_reader: Arc::new(Mutex::new(file)),
+            entries,
+        })
+    }
+
+    pub fn get_file_paths(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    pub fn get_entry_by_path(&self, path: &str) -> Option<&ZipEntry> {
+        self.entries.get(path)
+    }
+
+    fn get_entry_data_offset(&self, entry: &ZipEntry) -> std::io::Result<u64> {
+        let mut file_reader = self
+            .file_reader
+            .lock()
+            .map_err(|_| std::io::Error::from(ErrorKind::BrokenPipe))?;
+        file_reader.seek(SeekFrom::Start(entry.local_header_offset))?;
+        let local_file_header = LocalFileHeader::read(&mut *file_reader)
+            .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+        Ok(entry.local_header_offset
+            + 30
+            + local_file_header.file_name_length as u64
+            + local_file_header.extra_field_length as u64)
+    }
+
+    /// Return reader of the entry, a deflated entry is uncompressed into memory.
+    pub fn get_file_reader_by_path(
+        &self,
+        path: &str,
+    ) -> std::io::Result<Box<dyn UnityResource + Send + Sync>> {
+        let entry = self
+            .entries
+            .get(path)
+            .ok_or(std::io::Error::from(ErrorKind::NotFound))?;
+        let entry_reader = ResourceWindow::new(
+            self.file_reader.clone(),
+            self.get_entry_data_offset(entry)?,
+            entry.compressed_size,
+        );
+        match entry.compression_method {
+            COMPRESSION_METHOD_STORED => Ok(Box::new(entry_reader)),
+            COMPRESSION_METHOD_DEFLATED => {
+                let mut entry_data = Vec::with_capacity(entry.uncompressed_size as usize);
+                flate2::read::DeflateDecoder::new(entry_reader).read_to_end(&mut entry_data)?;
+                Ok(Box::new(Cursor::new(entry_data)))
+            }
+            compression_method => Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                format!("zip compression method {compression_method} is not supported"),
+            )),
+        }
+    }
+
+    /// Read at most `size` bytes from the start of the entry,
+    /// only the needed part of a deflated entry is uncompressed.
+    pub fn get_file_head_by_path(&self, path: &str, size: u64) -> std::io::Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .get(path)
+            .ok_or(std::io::Error::from(ErrorKind::NotFound))?;
+        let entry_reader = ResourceWindow::new(
+            self.file_reader.clone(),
+            self.get_entry_data_offset(entry)?,
+            entry.compressed_size,
+        );
+        let mut head = Vec::new();
+        match entry.compression_method {
+            COMPRESSION_METHOD_DEFLATED => flate2::read::DeflateDecoder::new(entry_reader)
+                .take(size)
+                .read_to_end(&mut head)?,
+            _ => entry_reader.take(size).read_to_end(&mut head)?,
+        };
+        Ok(head)
+    }
+
+    /// Open the entry at `path`, or join its split parts (`path.split0`, `path.split1`, ...)
+    /// if the entry does not exist.
+    pub fn open_resource_file(&self, path: &str) -> Option<Box<dyn UnityResource + Send + Sync>> {
+        if self.entries.contains_key(path) {
+            return self.get_file_reader_by_path(path).ok();
+        }
+        let mut parts = Vec::new();
+        while let Ok(part) = self.get_file_reader_by_path(&format!("{path}.split{}", parts.len())) {
+            parts.push(part);
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        SplitResource::new(parts)
+            .ok()
+            .map(|split_resource| Box::new(split_resource) as Box<dyn UnityResource + Send + Sync>)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use binrw::BinWrite;
+
+    pub(crate) fn build_zip(files: &[(&str, u16, &[u8])]) -> Vec<u8> {
+        let mut zip = Cursor::new(Vec::new());
+        let mut central_directory = Vec::new();
+        for (path, compression_method, data) in files {
+            let compressed_data = if *compression_method == COMPRESSION_METHOD_DEFLATED {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            } else {
+                data.to_vec()
+            };
+            let local_header_offset = zip.position() as u32;
+            LocalFileHeader {
+                version_needed: 20,
+                flags: 0,
+                compression_method: *compression_method,
+                last_modified_time: 0,
+                last_modified_date: 0,
+                crc32: 0,
+                compressed_size: compressed_data.len() as u32,
+                uncompressed_size: data.len() as u32,
+                file_name_length: path.len() as u16,
+                extra_field_length: 0,
+            }
+            .write(&mut zip)
+            .unwrap();
+            zip.write_all(path.as_bytes()).unwrap();
+            zip.write_all(&compressed_data).unwrap();
+            central_directory.push(CentralDirectoryHeader {
+                version_made_by: 20,
+                version_needed: 20,
+                flags: 0,
+                compression_method: *compression_method,
+                last_modified_time: 0,
+                last_modified_date: 0,
+                crc32: 0,
+                compressed_size: compressed_data.len() as u32,
+                uncompressed_size: data.len() as u32,
+                file_name_length: path.len() as u16,
+                extra_field_length: 0,
+                file_comment_length: 0,
+                disk_number_start: 0,
+                internal_file_attributes: 0,
+                external_file_attributes: 0,
+                local_header_offset,
+                file_name: path.as_bytes().to_vec(),
+                extra_field: Vec::new(),
+                file_comment: Vec::new(),
+            });
+        }
+        let central_directory_offset = zip.position() as u32;
+        for header in &central_directory {
+            header.write(&mut zip).unwrap();
+        }
+        EndOfCentralDirectory {
+            disk_number: 0,
+            central_directory_disk_number: 0,
+            disk_entries_count: central_directory.len() as u16,
+            entries_count: central_directory.len() as u16,
+            central_directory_size: zip.position() as u32 - central_directory_offset,
+            central_directory_offset,
+            comment_length: 0,
+        }
+        .write(&mut zip)
+        .unwrap();
+        zip.into_inner()
+    }
+
+    #[test]
+    fn test_zip_archive() {
+        let ress: Vec<u8> = (0..3000u32).map(|i| (i % 7) as u8).collect();
+        let level: Vec<u8> = (0..5000u32).map(|i| (i % 11) as u8).collect();
+        let zip = build_zip(&[
+            (
+                "assets/bin/Data/level0.resS",
+                COMPRESSION_METHOD_STORED,
+                &ress,
+            ),
+            (
+                "assets/bin/Data/level0.split0",
+                COMPRESSION_METHOD_STORED,
+                &level[..2000],
+            ),
+            (
+                "assets/bin/Data/level0.split1",
+                COMPRESSION_METHOD_DEFLATED,
+                &level[2000..],
+            ),
+            ("classes.dex", COMPRESSION_METHOD_DEFLATED, b"dex\n035\0"),
+        ]);
+        let zip_archive = ZipArchive::read(Box::new(Cursor::new(zip))).unwrap();
+        assert_eq!(zip_archive.get_file_paths().len(), 4);
+
+        let mut data = Vec::new();
+        zip_archive
+            .get_file_reader_by_path("assets/bin/Data/level0.resS")
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, ress);
+
+        let mut data = Vec::new();
+        zip_archive
+            .open_resource_file("assets/bin/Data/level0")
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, level);
+
+        assert_eq!(
+            zip_archive.get_file_head_by_path("classes.dex", 3).unwrap(),
+            b"dex"
+        );
+        assert!(zip_archive
+            .open_resource_file("assets/bin/Data/level1")
+            .is_none());
+    }
+}